@@ -4,7 +4,8 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use collector_v2::msg::{
-    HandleMsg, InitMsg, MigrateMsg, QueryCallersResponse, QueryMsg, QueryVersionResponse,
+    HandleMsg, InitMsg, MigrateMsg, OwnerResponse, QueryCallersResponse, QueryMsg,
+    QueryVersionResponse,
 };
 use collector_v2::state::State;
 
@@ -20,5 +21,6 @@ fn main() {
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(QueryCallersResponse), &out_dir);
+    export_schema(&schema_for!(OwnerResponse), &out_dir);
     export_schema(&schema_for!(QueryVersionResponse), &out_dir);
 }
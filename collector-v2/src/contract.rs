@@ -1,13 +1,27 @@
 use cosmwasm_std::{
-    entry_point, to_binary, Binary, Deps, DepsMut, Env, HandleResponse, InitResponse, MessageInfo,
-    MigrateResponse, StdResult,
+    attr, entry_point, to_binary, BankMsg, Binary, Deps, DepsMut, Empty, Env, HandleResponse,
+    HumanAddr, InitResponse, MessageInfo, MigrateResponse, Order, StdResult, Storage,
 };
 
 use crate::error::ContractError;
 use crate::msg::{
-    HandleMsg, InitMsg, MigrateMsg, QueryCallersResponse, QueryMsg, QueryVersionResponse,
+    HandleMsg, InitMsg, MigrateMsg, OwnerResponse, QueryCallersResponse, QueryMsg,
+    QueryVersionResponse,
 };
-use crate::state::{config, config_read, State};
+use crate::state::{
+    callers, callers_read, config, config_read, config_v1_read, contract_info, contract_info_read,
+    migrations, migrations_read, ContractVersion, Migration, State,
+};
+use semver::Version;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:collector-v2";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// V1_CONTRACT_NAME is the name collector stores in its contract info
+const V1_CONTRACT_NAME: &str = "crates.io:collector";
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 // Note, you can use StdResult in some functions where you do not
 // make use of the custom errors
@@ -15,13 +29,15 @@ use crate::state::{config, config_read, State};
 pub fn init(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     _msg: InitMsg,
 ) -> Result<InitResponse, ContractError> {
     let state = State {
-        callers: Vec::new(),
+        count: 0,
+        owner: info.sender,
     };
     config(deps.storage).save(&state)?;
+    set_contract_version(deps.storage)?;
 
     Ok(InitResponse::default())
 }
@@ -30,70 +46,214 @@ pub fn init(
 #[entry_point]
 pub fn handle(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: HandleMsg,
 ) -> Result<HandleResponse, ContractError> {
     match msg {
         HandleMsg::Call {} => try_call(deps, info),
+        HandleMsg::Withdraw {} => try_withdraw(deps, env, info),
     }
 }
 
 pub fn try_call(deps: DepsMut, info: MessageInfo) -> Result<HandleResponse, ContractError> {
-    config(deps.storage).update(|mut state| -> Result<_, ContractError> {
-        if !state.callers.contains(&info.sender) {
-            state.callers.push(info.sender);
-        }
-        Ok(state)
-    })?;
+    let address = info.sender.as_bytes();
+    if callers_read(deps.storage).may_load(address)?.is_none() {
+        callers(deps.storage).save(address, &Empty {})?;
+        config(deps.storage).update(|mut state| -> StdResult<_> {
+            state.count += 1;
+            Ok(state)
+        })?;
+    }
 
     Ok(HandleResponse::default())
 }
 
+pub fn try_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(deps.storage).load()?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let balance = deps
+        .querier
+        .query_all_balances(env.contract.address.clone())?;
+    let mut res = HandleResponse::default();
+    if !balance.is_empty() {
+        res.messages.push(
+            BankMsg::Send {
+                from_address: env.contract.address,
+                to_address: state.owner,
+                amount: balance,
+            }
+            .into(),
+        );
+    }
+    Ok(res)
+}
+
 #[entry_point]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::GetCallers {} => to_binary(&query_count(deps)?),
-        QueryMsg::GetVersion {} => to_binary(&query_version()?),
+        QueryMsg::GetCallers { start_after, limit } => {
+            to_binary(&query_callers(deps, start_after, limit)?)
+        }
+        QueryMsg::GetOwner {} => to_binary(&query_owner(deps)?),
+        QueryMsg::GetVersion {} => to_binary(&query_version(deps)?),
     }
 }
 
+// migrate takes over the owner and the callers of a collector, or of an older collector-v2.
+// Unversioned collectors and collector-v2 up to 0.2.0 had no owner, the sender of the
+// migration becomes the owner.
+// collector-v2 has no calling policy, so the policy, allowlist, hook, ttl and removed callers
+// of a collector are dropped and anyone may call after the migration. The calls recorded for
+// each caller and the leaderboard indexes stay in storage unread, removing them would cost gas
+// for every caller.
 #[entry_point]
 pub fn migrate(
-    _deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     _msg: MigrateMsg,
 ) -> Result<MigrateResponse, ContractError> {
-    //TODO
-    // let old_data = config(deps.storage).load()?;
-    // config_v1(deps.storage).update(|mut state| -> Result<_, ContractError> {
-    //     old_data
-    //         .callers
-    //         .iter()
-    //         .for_each(|x| state.callers.push(x.clone()));
-    //     state.version = 1;
-    //     Ok(state)
-    // })?;
-    Ok(MigrateResponse::default())
-}
+    let stored = contract_info_read(deps.storage).may_load()?;
+    match &stored {
+        // collectors that kept their callers in the config stored no contract info
+        None => {
+            let callers = config_v1_read(deps.storage)
+                .load()
+                .map_err(|_| ContractError::UnknownContract {})?
+                .callers;
+            take_over_callers(deps.storage, info.sender, callers)?;
+        }
+        // the callers bucket of collector is kept as is, only the fields of its config
+        // that are carried on are saved again
+        Some(stored) if stored.contract == V1_CONTRACT_NAME => {
+            let state = config_read(deps.storage).load()?;
+            config(deps.storage).save(&state)?;
+        }
+        Some(stored) if stored.contract == CONTRACT_NAME => {
+            let version = Version::parse(&stored.version)?;
+            if version > Version::parse(CONTRACT_VERSION)? {
+                return Err(ContractError::CannotDowngrade {
+                    stored: stored.version.clone(),
+                    current: CONTRACT_VERSION.into(),
+                });
+            }
+            // up to 0.2.0 the callers were kept in the config
+            if version < Version::new(0, 3, 0) {
+                let callers = config_v1_read(deps.storage).load()?.callers;
+                take_over_callers(deps.storage, info.sender, callers)?;
+            }
+        }
+        Some(stored) => {
+            return Err(ContractError::CannotMigrate {
+                contract: stored.contract.clone(),
+            })
+        }
+    };
 
-fn query_count(deps: Deps) -> StdResult<QueryCallersResponse> {
+    let mut history = migrations_read(deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    history.push(Migration {
+        from: stored.clone(),
+        to: code_version(),
+        height: env.block.height,
+    });
+    set_contract_version(deps.storage)?;
+    migrations(deps.storage).save(&history)?;
+
+    let from = stored.map_or_else(
+        || "unversioned".to_string(),
+        |stored| format!("{} {}", stored.contract, stored.version),
+    );
     let state = config_read(deps.storage).load()?;
-    Ok(QueryCallersResponse {
-        callers: state.callers,
+    Ok(MigrateResponse {
+        attributes: vec![
+            attr("method", "migrate"),
+            attr("from", from),
+            attr("callers", state.count),
+        ],
+        ..MigrateResponse::default()
+    })
+}
+
+// take_over_callers moves callers kept in the config into the callers bucket
+fn take_over_callers(
+    storage: &mut dyn Storage,
+    owner: HumanAddr,
+    list: Vec<HumanAddr>,
+) -> StdResult<()> {
+    for address in &list {
+        callers(storage).save(address.as_bytes(), &Empty {})?;
+    }
+    config(storage).save(&State {
+        count: list.len() as u64,
+        owner,
     })
 }
 
-fn query_version() -> StdResult<QueryVersionResponse> {
-    Ok(QueryVersionResponse { version: 1 })
+fn set_contract_version(storage: &mut dyn Storage) -> StdResult<()> {
+    contract_info(storage).save(&code_version())
+}
+
+fn code_version() -> ContractVersion {
+    ContractVersion {
+        contract: CONTRACT_NAME.into(),
+        version: CONTRACT_VERSION.into(),
+    }
+}
+
+fn query_callers(
+    deps: Deps,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<QueryCallersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // the smallest key after start_after
+    let start = start_after.map(|address| {
+        let mut key = address.as_bytes().to_vec();
+        key.push(0);
+        key
+    });
+    let callers = callers_read(deps.storage)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, _) = item?;
+            Ok(HumanAddr(String::from_utf8(key)?))
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(QueryCallersResponse { callers })
+}
+
+fn query_owner(deps: Deps) -> StdResult<OwnerResponse> {
+    let state = config_read(deps.storage).load()?;
+    Ok(OwnerResponse { owner: state.owner })
+}
+
+fn query_version(deps: Deps) -> StdResult<QueryVersionResponse> {
+    Ok(QueryVersionResponse {
+        stored: contract_info_read(deps.storage).load()?,
+        code: code_version(),
+        migrations: migrations_read(deps.storage)
+            .may_load()?
+            .unwrap_or_default(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::{V1State, CONFIG_KEY};
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
+    use cosmwasm_std::{coins, from_binary, CosmosMsg};
+    use cosmwasm_storage::singleton;
 
     #[test]
     fn proper_initialization() {
@@ -105,6 +265,11 @@ mod tests {
         // we can just call .unwrap() to assert this was a success
         let res = init(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVersion {}).unwrap();
+        let value: QueryVersionResponse = from_binary(&res).unwrap();
+        assert_eq!(code_version(), value.stored);
+        assert!(value.migrations.is_empty());
     }
 
     #[test]
@@ -121,8 +286,177 @@ mod tests {
         let _res = handle(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // should increase counter by 1
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCallers {}).unwrap();
-        let value: QueryCallersResponse = from_binary(&res).unwrap();
-        assert_eq!(1, value.callers.len());
+        assert_eq!(1, callers(deps.as_ref()).len());
+    }
+
+    fn callers(deps: Deps) -> Vec<HumanAddr> {
+        let msg = QueryMsg::GetCallers {
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps, mock_env(), msg).unwrap();
+        from_binary::<QueryCallersResponse>(&res).unwrap().callers
+    }
+
+    fn owner(deps: Deps) -> HumanAddr {
+        let res = query(deps, mock_env(), QueryMsg::GetOwner {}).unwrap();
+        from_binary::<OwnerResponse>(&res).unwrap().owner
+    }
+
+    #[test]
+    fn migrate_from_collector() {
+        let mut deps = mock_dependencies(&coins(6, "token"));
+        let info = mock_info("creator", &[]);
+        collector::contract::init(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            collector::msg::InitMsg::default(),
+        )
+        .unwrap();
+        for caller in ["bob", "alice", "bob"].iter() {
+            let info = mock_info(*caller, &coins(2, "token"));
+            let msg = collector::msg::HandleMsg::Call { callback: None };
+            collector::contract::handle(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let info = mock_info("admin", &[]);
+        let res = migrate(deps.as_mut(), mock_env(), info, MigrateMsg {}).unwrap();
+        assert_eq!(attr("from", "crates.io:collector 0.1.0"), res.attributes[1]);
+        assert_eq!(attr("callers", 2), res.attributes[2]);
+        assert_eq!(
+            vec![HumanAddr::from("alice"), HumanAddr::from("bob")],
+            callers(deps.as_ref())
+        );
+        let stored = contract_info_read(&deps.storage).load().unwrap();
+        assert_eq!(CONTRACT_NAME, stored.contract);
+        assert_eq!(
+            State {
+                count: 2,
+                owner: "creator".into(),
+            },
+            config_read(&deps.storage).load().unwrap()
+        );
+
+        // the owner of the collector can still withdraw the fees it collected
+        let info = mock_info("admin", &[]);
+        let err = handle(deps.as_mut(), mock_env(), info, HandleMsg::Withdraw {}).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let info = mock_info("creator", &[]);
+        let res = handle(deps.as_mut(), mock_env(), info, HandleMsg::Withdraw {}).unwrap();
+        assert_eq!(
+            vec![CosmosMsg::from(BankMsg::Send {
+                from_address: mock_env().contract.address,
+                to_address: "creator".into(),
+                amount: coins(6, "token"),
+            })],
+            res.messages
+        );
+
+        // known callers are not counted twice
+        for caller in ["alice", "carol"].iter() {
+            let info = mock_info(*caller, &[]);
+            handle(deps.as_mut(), mock_env(), info, HandleMsg::Call {}).unwrap();
+        }
+        assert_eq!(3, callers(deps.as_ref()).len());
+        assert_eq!(3, config_read(&deps.storage).load().unwrap().count);
+
+        // migrating to a newer collector-v2 keeps the history
+        let mut env = mock_env();
+        env.block.height += 100;
+        let info = mock_info("admin", &[]);
+        migrate(deps.as_mut(), env.clone(), info, MigrateMsg {}).unwrap();
+        assert_eq!(3, callers(deps.as_ref()).len());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVersion {}).unwrap();
+        let value: QueryVersionResponse = from_binary(&res).unwrap();
+        assert_eq!(code_version(), value.stored);
+        assert_eq!(code_version(), value.code);
+        assert_eq!(
+            vec![
+                Migration {
+                    from: Some(ContractVersion {
+                        contract: "crates.io:collector".into(),
+                        version: "0.1.0".into(),
+                    }),
+                    to: code_version(),
+                    height: mock_env().block.height,
+                },
+                Migration {
+                    from: Some(code_version()),
+                    to: code_version(),
+                    height: env.block.height,
+                },
+            ],
+            value.migrations
+        );
+    }
+
+    #[test]
+    fn migrate_from_unversioned_collector() {
+        let mut deps = mock_dependencies(&[]);
+        let state = V1State {
+            callers: vec!["alice".into(), "bob".into()],
+        };
+        singleton(&mut deps.storage, CONFIG_KEY)
+            .save(&state)
+            .unwrap();
+
+        let info = mock_info("admin", &[]);
+        let res = migrate(deps.as_mut(), mock_env(), info, MigrateMsg {}).unwrap();
+        assert_eq!(attr("from", "unversioned"), res.attributes[1]);
+        assert_eq!(state.callers, callers(deps.as_ref()));
+        assert_eq!(HumanAddr::from("admin"), owner(deps.as_ref()));
+    }
+
+    #[test]
+    fn migrate_from_callers_in_config() {
+        let mut deps = mock_dependencies(&[]);
+        contract_info(&mut deps.storage)
+            .save(&ContractVersion {
+                contract: CONTRACT_NAME.into(),
+                version: "0.2.0".into(),
+            })
+            .unwrap();
+        let state = V1State {
+            callers: vec!["alice".into(), "bob".into()],
+        };
+        singleton(&mut deps.storage, CONFIG_KEY)
+            .save(&state)
+            .unwrap();
+
+        let info = mock_info("admin", &[]);
+        let res = migrate(deps.as_mut(), mock_env(), info, MigrateMsg {}).unwrap();
+        assert_eq!(attr("callers", 2), res.attributes[2]);
+        assert_eq!(state.callers, callers(deps.as_ref()));
+        assert_eq!(HumanAddr::from("admin"), owner(deps.as_ref()));
+    }
+
+    #[test]
+    fn migrate_refused() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("admin", &[]);
+        let err = migrate(deps.as_mut(), mock_env(), info.clone(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::UnknownContract {}));
+
+        contract_info(&mut deps.storage)
+            .save(&ContractVersion {
+                contract: "crates.io:escrow".into(),
+                version: "0.1.0".into(),
+            })
+            .unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), info.clone(), MigrateMsg {}).unwrap_err();
+        match err {
+            ContractError::CannotMigrate { contract } => assert_eq!("crates.io:escrow", contract),
+            err => panic!("unexpected error: {}", err),
+        }
+
+        contract_info(&mut deps.storage)
+            .save(&ContractVersion {
+                contract: CONTRACT_NAME.into(),
+                version: "9.0.0".into(),
+            })
+            .unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), info, MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::CannotDowngrade { .. }));
     }
 }
@@ -6,8 +6,20 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Semver(#[from] semver::Error),
+
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Cannot migrate from {contract}")]
+    CannotMigrate { contract: String },
+
+    #[error("Cannot migrate from an unknown contract")]
+    UnknownContract {},
+
+    #[error("Cannot migrate from version {stored} to older version {current}")]
+    CannotDowngrade { stored: String, current: String },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::HumanAddr;
 
+use crate::state::{ContractVersion, Migration};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {}
 
@@ -10,13 +12,22 @@ pub struct InitMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
     Call {},
+    // Withdraw sends the balance of the contract to the owner, only the owner can do it
+    Withdraw {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    // GetCount returns the current count as a json-encoded number
-    GetCallers {},
+    // GetCallers returns the callers after start_after, in address order
+    GetCallers {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    // GetOwner returns the owner of the contract
+    GetOwner {},
+    // GetVersion returns the stored contract version, the version of the running code
+    // and the migrations of the contract
     GetVersion {},
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -29,7 +40,14 @@ pub struct QueryCallersResponse {
     pub callers: Vec<HumanAddr>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnerResponse {
+    pub owner: HumanAddr,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct QueryVersionResponse {
-    pub version: u8,
+    pub stored: ContractVersion,
+    pub code: ContractVersion,
+    pub migrations: Vec<Migration>,
 }
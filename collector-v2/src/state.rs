@@ -1,20 +1,83 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Storage};
-use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
+use cosmwasm_std::{Empty, HumanAddr, Storage};
+use cosmwasm_storage::{
+    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
+    Singleton,
+};
 
 pub static CONFIG_KEY: &[u8] = b"config";
+pub static CONTRACT_INFO_KEY: &[u8] = b"contract_info";
+pub static MIGRATIONS_KEY: &[u8] = b"migrations";
+pub static CALLERS_KEY: &[u8] = b"callers";
 
+// State keeps the fields of the collector config that collector-v2 carries on,
+// so the config of a collector loads as a State
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
+    // count is the number of addresses in the callers bucket
+    pub count: u64,
+    pub owner: HumanAddr,
+}
+
+// ContractVersion identifies the contract owning the storage, for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractVersion {
+    pub contract: String,
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Migration {
+    // from is the contract info found by the migration, unversioned collectors had none
+    pub from: Option<ContractVersion>,
+    pub to: ContractVersion,
+    pub height: u64,
+}
+
+// V1State is the layout of the config of collector before it kept its callers in a bucket,
+// such collectors have no contract info. collector-v2 used it up to version 0.2.0.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct V1State {
     pub callers: Vec<HumanAddr>,
 }
 
-pub fn config(storage: &mut dyn Storage) -> Singleton<State> {
+pub fn config(storage: &mut dyn Storage) -> Singleton<'_, State> {
     singleton(storage, CONFIG_KEY)
 }
 
-pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<State> {
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, State> {
+    singleton_read(storage, CONFIG_KEY)
+}
+
+pub fn contract_info(storage: &mut dyn Storage) -> Singleton<'_, ContractVersion> {
+    singleton(storage, CONTRACT_INFO_KEY)
+}
+
+pub fn contract_info_read(storage: &dyn Storage) -> ReadonlySingleton<'_, ContractVersion> {
+    singleton_read(storage, CONTRACT_INFO_KEY)
+}
+
+// migrations holds every migration of the contract, oldest first
+pub fn migrations(storage: &mut dyn Storage) -> Singleton<'_, Vec<Migration>> {
+    singleton(storage, MIGRATIONS_KEY)
+}
+
+pub fn migrations_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Vec<Migration>> {
+    singleton_read(storage, MIGRATIONS_KEY)
+}
+
+pub fn config_v1_read(storage: &dyn Storage) -> ReadonlySingleton<'_, V1State> {
     singleton_read(storage, CONFIG_KEY)
 }
+
+// callers is keyed by the address of every caller. It is the callers bucket of collector,
+// whose values are not read.
+pub fn callers(storage: &mut dyn Storage) -> Bucket<'_, Empty> {
+    bucket(storage, CALLERS_KEY)
+}
+
+pub fn callers_read(storage: &dyn Storage) -> ReadonlyBucket<'_, Empty> {
+    bucket_read(storage, CALLERS_KEY)
+}
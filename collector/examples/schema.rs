@@ -3,8 +3,12 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use collector::msg::{HandleMsg, InitMsg, QueryCallersResponse, QueryMsg};
-use collector::state::State;
+use collector::msg::{
+    CallerCountResponse, CallerInfoResponse, HandleMsg, HookResponse, InitMsg, IsAllowedResponse,
+    IsCallerResponse, IsRemovedResponse, LeaderboardResponse, PolicyResponse, QueryCallersResponse,
+    QueryMsg, TtlResponse,
+};
+use collector::state::{CallerInfo, State};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -16,5 +20,15 @@ fn main() {
     export_schema(&schema_for!(HandleMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
+    export_schema(&schema_for!(CallerInfo), &out_dir);
     export_schema(&schema_for!(QueryCallersResponse), &out_dir);
+    export_schema(&schema_for!(IsCallerResponse), &out_dir);
+    export_schema(&schema_for!(CallerCountResponse), &out_dir);
+    export_schema(&schema_for!(CallerInfoResponse), &out_dir);
+    export_schema(&schema_for!(LeaderboardResponse), &out_dir);
+    export_schema(&schema_for!(PolicyResponse), &out_dir);
+    export_schema(&schema_for!(IsAllowedResponse), &out_dir);
+    export_schema(&schema_for!(IsRemovedResponse), &out_dir);
+    export_schema(&schema_for!(HookResponse), &out_dir);
+    export_schema(&schema_for!(TtlResponse), &out_dir);
 }
@@ -1,23 +1,51 @@
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, HandleResponse, InitResponse, MessageInfo, StdResult,
+    to_binary, BankMsg, Binary, Coin, Deps, DepsMut, Empty, Env, HandleResponse, HumanAddr,
+    InitResponse, MessageInfo, Order, StdResult, Storage, Uint128, WasmMsg,
 };
 
 use crate::error::ContractError;
-use crate::msg::{HandleMsg, InitMsg, QueryCallersResponse, QueryMsg};
-use crate::state::{config, config_read, State};
+use crate::msg::{
+    CallerCountResponse, CallerInfoResponse, CallerStatus, HandleMsg, HookResponse, InitMsg,
+    IsAllowedResponse, IsCallerResponse, IsRemovedResponse, LeaderboardOrder, LeaderboardResponse,
+    Policy, PolicyResponse, QueryCallersResponse, QueryMsg, TtlResponse,
+};
+use crate::state::{
+    allowlist, allowlist_read, callers, callers_read, config, config_read, contract_info, index,
+    index_read, removed, removed_read, CallerInfo, ContractVersion, State,
+};
+
+// version info for migration info
+pub const CONTRACT_NAME: &str = "crates.io:collector";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 // Note, you can use StdResult in some functions where you do not
 // make use of the custom errors
 pub fn init(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InitMsg,
+    info: MessageInfo,
+    msg: InitMsg,
 ) -> Result<InitResponse, ContractError> {
+    let policy = msg.policy.unwrap_or(Policy::Open {});
+    validate_policy(&policy)?;
     let state = State {
-        callers: Vec::new(),
+        count: 0,
+        owner: info.sender,
+        policy,
+        hook: msg.hook,
+        ttl: msg.ttl,
     };
     config(deps.storage).save(&state)?;
+    for address in msg.allowlist.unwrap_or_default() {
+        allowlist(deps.storage).save(address.as_bytes(), &Empty {})?;
+    }
+    contract_info(deps.storage).save(&ContractVersion {
+        contract: CONTRACT_NAME.into(),
+        version: CONTRACT_VERSION.into(),
+    })?;
 
     Ok(InitResponse::default())
 }
@@ -25,50 +53,511 @@ pub fn init(
 // And declare a custom Error variant for the ones where you will want to make use of it
 pub fn handle(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: HandleMsg,
 ) -> Result<HandleResponse, ContractError> {
     match msg {
-        HandleMsg::Call {} => try_call(deps, info),
+        HandleMsg::Call { callback } => try_call(deps, env, info, callback.unwrap_or(false)),
+        HandleMsg::UpdatePolicy { policy } => try_update_policy(deps, info, policy),
+        HandleMsg::UpdateAllowlist { add, remove } => try_update_allowlist(deps, info, add, remove),
+        HandleMsg::UpdateHook { hook } => try_update_hook(deps, info, hook),
+        HandleMsg::Deregister {} => try_deregister(deps, info),
+        HandleMsg::RemoveCallers { callers } => try_remove_callers(deps, info, callers),
+        HandleMsg::RestoreCallers { callers } => try_restore_callers(deps, info, callers),
+        HandleMsg::UpdateTtl { ttl } => try_update_ttl(deps, info, ttl),
+        HandleMsg::Withdraw {} => try_withdraw(deps, env, info),
+    }
+}
+
+pub fn try_call(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback: bool,
+) -> Result<HandleResponse, ContractError> {
+    let address = &info.sender;
+    let mut state = config_read(deps.storage).load()?;
+    assert_may_call(deps.as_ref(), &state.policy, &info)?;
+    let previous = callers_read(deps.storage).may_load(address.as_bytes())?;
+    let mut caller = match &previous {
+        Some(caller) => caller.clone(),
+        None => {
+            state.count += 1;
+            config(deps.storage).save(&state)?;
+            let order = LeaderboardOrder::Earliest {};
+            index(deps.storage, &order).save(&index_key(env.block.height, address), &Empty {})?;
+            CallerInfo {
+                first_height: env.block.height,
+                first_time: env.block.time,
+                last_height: env.block.height,
+                last_time: env.block.time,
+                calls: 0,
+                funds: vec![],
+            }
+        }
+    };
+    caller.last_height = env.block.height;
+    caller.last_time = env.block.time;
+    caller.calls += 1;
+    for coin in &info.sent_funds {
+        match caller.funds.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(total) => total.amount += coin.amount,
+            None => caller.funds.push(coin.clone()),
+        }
+    }
+
+    // move the caller within the leaderboard indexes
+    let calls = LeaderboardOrder::Calls {};
+    let recent = LeaderboardOrder::Recent {};
+    if let Some(previous) = &previous {
+        index(deps.storage, &calls).remove(&index_key(previous.calls, address));
+        index(deps.storage, &recent).remove(&index_key(previous.last_height, address));
+    }
+    index(deps.storage, &calls).save(&index_key(caller.calls, address), &Empty {})?;
+    index(deps.storage, &recent).save(&index_key(caller.last_height, address), &Empty {})?;
+    for coin in &info.sent_funds {
+        let previous = previous.as_ref().and_then(|previous| {
+            previous
+                .funds
+                .iter()
+                .find(|c| c.denom == coin.denom)
+                .map(|c| c.amount)
+        });
+        update_funds_index(deps.storage, &coin.denom, previous, &caller, address)?;
+    }
+    callers(deps.storage).save(address.as_bytes(), &caller)?;
+
+    let mut res = HandleResponse::default();
+    match &state.hook {
+        Some(hook) if callback && previous.is_none() => {
+            res.messages.push(
+                WasmMsg::Execute {
+                    contract_addr: info.sender,
+                    msg: hook.clone(),
+                    send: vec![],
+                }
+                .into(),
+            );
+        }
+        _ => {}
     }
+    Ok(res)
 }
 
-pub fn try_call(deps: DepsMut, info: MessageInfo) -> Result<HandleResponse, ContractError> {
+fn assert_may_call(deps: Deps, policy: &Policy, info: &MessageInfo) -> Result<(), ContractError> {
+    if removed_read(deps.storage)
+        .may_load(info.sender.as_bytes())?
+        .is_some()
+    {
+        return Err(ContractError::Removed {
+            address: info.sender.clone(),
+        });
+    }
+    match policy {
+        Policy::Open {} => Ok(()),
+        Policy::Allowlist {} => {
+            let listed = allowlist_read(deps.storage).may_load(info.sender.as_bytes())?;
+            listed.map(|_| ()).ok_or(ContractError::Unauthorized {})
+        }
+        Policy::Fee { fee } => {
+            if funds_of(&info.sent_funds, &fee.denom) < fee.amount {
+                return Err(ContractError::InsufficientFee {
+                    amount: fee.amount,
+                    denom: fee.denom.clone(),
+                });
+            }
+            Ok(())
+        }
+    }
+}
+
+fn validate_policy(policy: &Policy) -> Result<(), ContractError> {
+    match policy {
+        Policy::Fee { fee } if fee.amount.is_zero() => Err(ContractError::InvalidFee {}),
+        _ => Ok(()),
+    }
+}
+
+pub fn try_update_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    policy: Policy,
+) -> Result<HandleResponse, ContractError> {
+    validate_policy(&policy)?;
     config(deps.storage).update(|mut state| -> Result<_, ContractError> {
-        if !state.callers.contains(&info.sender) {
-            state.callers.push(info.sender);
+        if info.sender != state.owner {
+            return Err(ContractError::Unauthorized {});
         }
+        state.policy = policy;
         Ok(state)
     })?;
 
     Ok(HandleResponse::default())
 }
 
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetCallers {} => to_binary(&query_count(deps)?),
+pub fn try_update_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    hook: Option<Binary>,
+) -> Result<HandleResponse, ContractError> {
+    config(deps.storage).update(|mut state| -> Result<_, ContractError> {
+        if info.sender != state.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+        state.hook = hook;
+        Ok(state)
+    })?;
+
+    Ok(HandleResponse::default())
+}
+
+pub fn try_update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<HumanAddr>,
+    remove: Vec<HumanAddr>,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(deps.storage).load()?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    for address in remove {
+        allowlist(deps.storage).remove(address.as_bytes());
+    }
+    for address in add {
+        allowlist(deps.storage).save(address.as_bytes(), &Empty {})?;
+    }
+
+    Ok(HandleResponse::default())
+}
+
+pub fn try_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(deps.storage).load()?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let balance = deps
+        .querier
+        .query_all_balances(env.contract.address.clone())?;
+    let mut res = HandleResponse::default();
+    if !balance.is_empty() {
+        res.messages.push(
+            BankMsg::Send {
+                from_address: env.contract.address,
+                to_address: state.owner,
+                amount: balance,
+            }
+            .into(),
+        );
+    }
+    Ok(res)
+}
+
+pub fn try_deregister(deps: DepsMut, info: MessageInfo) -> Result<HandleResponse, ContractError> {
+    remove_caller(deps.storage, &info.sender)?;
+
+    Ok(HandleResponse::default())
+}
+
+pub fn try_remove_callers(
+    deps: DepsMut,
+    info: MessageInfo,
+    callers: Vec<HumanAddr>,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(deps.storage).load()?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    // addresses that are not callers are blocked ahead of their first call
+    for address in &callers {
+        if callers_read(deps.storage)
+            .may_load(address.as_bytes())?
+            .is_some()
+        {
+            remove_caller(deps.storage, address)?;
+        }
+        removed(deps.storage).save(address.as_bytes(), &Empty {})?;
     }
+
+    Ok(HandleResponse::default())
 }
 
-fn query_count(deps: Deps) -> StdResult<QueryCallersResponse> {
+pub fn try_restore_callers(
+    deps: DepsMut,
+    info: MessageInfo,
+    callers: Vec<HumanAddr>,
+) -> Result<HandleResponse, ContractError> {
     let state = config_read(deps.storage).load()?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    for address in &callers {
+        removed(deps.storage).remove(address.as_bytes());
+    }
+
+    Ok(HandleResponse::default())
+}
+
+pub fn try_update_ttl(
+    deps: DepsMut,
+    info: MessageInfo,
+    ttl: Option<u64>,
+) -> Result<HandleResponse, ContractError> {
+    config(deps.storage).update(|mut state| -> Result<_, ContractError> {
+        if info.sender != state.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+        state.ttl = ttl;
+        Ok(state)
+    })?;
+
+    Ok(HandleResponse::default())
+}
+
+// remove_caller forgets everything recorded for address, it may call again later
+fn remove_caller(storage: &mut dyn Storage, address: &HumanAddr) -> Result<(), ContractError> {
+    let caller = callers_read(storage)
+        .may_load(address.as_bytes())?
+        .ok_or_else(|| ContractError::NotCaller {
+            address: address.clone(),
+        })?;
+    let keys = [
+        (LeaderboardOrder::Calls {}, caller.calls),
+        (LeaderboardOrder::Recent {}, caller.last_height),
+        (LeaderboardOrder::Earliest {}, caller.first_height),
+    ];
+    for (order, value) in keys.iter() {
+        index(storage, order).remove(&index_key(*value, address));
+    }
+    for coin in &caller.funds {
+        let order = LeaderboardOrder::Funds {
+            denom: coin.denom.clone(),
+        };
+        index(storage, &order).remove(&funds_key(coin.amount, address));
+    }
+    callers(storage).remove(address.as_bytes());
+    config(storage).update(|mut state| -> StdResult<_> {
+        state.count -= 1;
+        Ok(state)
+    })?;
+    Ok(())
+}
+
+// is_active returns whether caller called within ttl of time
+fn is_active(caller: &CallerInfo, ttl: Option<u64>, time: u64) -> bool {
+    ttl.map_or(true, |ttl| caller.last_time.saturating_add(ttl) >= time)
+}
+
+fn update_funds_index(
+    storage: &mut dyn Storage,
+    denom: &str,
+    previous: Option<Uint128>,
+    caller: &CallerInfo,
+    address: &HumanAddr,
+) -> StdResult<()> {
+    let order = LeaderboardOrder::Funds {
+        denom: denom.into(),
+    };
+    let mut index = index(storage, &order);
+    if let Some(previous) = previous {
+        index.remove(&funds_key(previous, address));
+    }
+    index.save(
+        &funds_key(funds_of(&caller.funds, denom), address),
+        &Empty {},
+    )
+}
+
+fn funds_of(funds: &[Coin], denom: &str) -> Uint128 {
+    funds
+        .iter()
+        .find(|coin| coin.denom == denom)
+        .map_or_else(Uint128::zero, |coin| coin.amount)
+}
+
+fn index_key(value: u64, address: &HumanAddr) -> Vec<u8> {
+    [&value.to_be_bytes()[..], address.as_bytes()].concat()
+}
+
+fn funds_key(amount: Uint128, address: &HumanAddr) -> Vec<u8> {
+    [&amount.u128().to_be_bytes()[..], address.as_bytes()].concat()
+}
+
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetCallers {
+            start_after,
+            limit,
+            status,
+        } => to_binary(&query_callers(deps, env, start_after, limit, status)?),
+        QueryMsg::IsCaller { address } => to_binary(&query_is_caller(deps, address)?),
+        QueryMsg::GetCallerCount {} => to_binary(&query_count(deps)?),
+        QueryMsg::GetPolicy {} => to_binary(&query_policy(deps)?),
+        QueryMsg::GetHook {} => to_binary(&query_hook(deps)?),
+        QueryMsg::GetTtl {} => to_binary(&query_ttl(deps)?),
+        QueryMsg::IsAllowed { address } => to_binary(&query_is_allowed(deps, address)?),
+        QueryMsg::IsRemoved { address } => to_binary(&query_is_removed(deps, address)?),
+        QueryMsg::GetCallerInfo { address } => to_binary(&query_caller_info(deps, env, address)?),
+        QueryMsg::GetLeaderboard { order, limit } => {
+            to_binary(&query_leaderboard(deps, env, order, limit)?)
+        }
+    }
+}
+
+fn query_callers(
+    deps: Deps,
+    env: Env,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+    status: Option<CallerStatus>,
+) -> StdResult<QueryCallersResponse> {
+    let ttl = config_read(deps.storage).load()?.ttl;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // the smallest key after start_after
+    let start = start_after.map(|address| {
+        let mut key = address.as_bytes().to_vec();
+        key.push(0);
+        key
+    });
+    // at most limit callers are read, whether or not they match status
+    let scanned = callers_read(deps.storage)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, caller) = item?;
+            Ok((HumanAddr(String::from_utf8(key)?), caller))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let next_start_after = if scanned.len() == limit {
+        scanned.last().map(|(address, _)| address.clone())
+    } else {
+        None
+    };
+    let callers = scanned
+        .into_iter()
+        .filter(|(_, caller)| match &status {
+            Some(status) => {
+                is_active(caller, ttl, env.block.time) == (*status == CallerStatus::Active)
+            }
+            None => true,
+        })
+        .map(|(address, _)| address)
+        .collect();
     Ok(QueryCallersResponse {
-        callers: state.callers,
+        callers,
+        next_start_after,
+    })
+}
+
+fn query_is_caller(deps: Deps, address: HumanAddr) -> StdResult<IsCallerResponse> {
+    let caller = callers_read(deps.storage).may_load(address.as_bytes())?;
+    Ok(IsCallerResponse {
+        is_caller: caller.is_some(),
+    })
+}
+
+fn query_count(deps: Deps) -> StdResult<CallerCountResponse> {
+    let state = config_read(deps.storage).load()?;
+    Ok(CallerCountResponse { count: state.count })
+}
+
+fn query_policy(deps: Deps) -> StdResult<PolicyResponse> {
+    let state = config_read(deps.storage).load()?;
+    Ok(PolicyResponse {
+        owner: state.owner,
+        policy: state.policy,
+    })
+}
+
+fn query_hook(deps: Deps) -> StdResult<HookResponse> {
+    let state = config_read(deps.storage).load()?;
+    Ok(HookResponse { hook: state.hook })
+}
+
+fn query_is_allowed(deps: Deps, address: HumanAddr) -> StdResult<IsAllowedResponse> {
+    let listed = allowlist_read(deps.storage).may_load(address.as_bytes())?;
+    Ok(IsAllowedResponse {
+        allowed: listed.is_some(),
     })
 }
 
+fn query_is_removed(deps: Deps, address: HumanAddr) -> StdResult<IsRemovedResponse> {
+    let listed = removed_read(deps.storage).may_load(address.as_bytes())?;
+    Ok(IsRemovedResponse {
+        removed: listed.is_some(),
+    })
+}
+
+fn query_ttl(deps: Deps) -> StdResult<TtlResponse> {
+    let state = config_read(deps.storage).load()?;
+    Ok(TtlResponse { ttl: state.ttl })
+}
+
+fn query_caller_info(deps: Deps, env: Env, address: HumanAddr) -> StdResult<CallerInfoResponse> {
+    let ttl = config_read(deps.storage).load()?.ttl;
+    let caller = callers_read(deps.storage).load(address.as_bytes())?;
+    let active = is_active(&caller, ttl, env.block.time);
+    Ok(caller_response(address, caller, active))
+}
+
+// query_leaderboard walks the index of order, callers with equal values
+// are returned in reverse address order, except for Earliest
+fn query_leaderboard(
+    deps: Deps,
+    env: Env,
+    order: LeaderboardOrder,
+    limit: Option<u32>,
+) -> StdResult<LeaderboardResponse> {
+    let ttl = config_read(deps.storage).load()?.ttl;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (direction, width) = match order {
+        LeaderboardOrder::Earliest {} => (Order::Ascending, 8),
+        LeaderboardOrder::Calls {} | LeaderboardOrder::Recent {} => (Order::Descending, 8),
+        LeaderboardOrder::Funds { .. } => (Order::Descending, 16),
+    };
+    let callers = index_read(deps.storage, &order)
+        .range(None, None, direction)
+        .take(limit)
+        .map(|item| {
+            let (key, _) = item?;
+            let address = HumanAddr(String::from_utf8(key[width..].to_vec())?);
+            let caller = callers_read(deps.storage).load(address.as_bytes())?;
+            let active = is_active(&caller, ttl, env.block.time);
+            Ok(caller_response(address, caller, active))
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(LeaderboardResponse { callers })
+}
+
+fn caller_response(address: HumanAddr, caller: CallerInfo, active: bool) -> CallerInfoResponse {
+    CallerInfoResponse {
+        address,
+        first_height: caller.first_height,
+        first_time: caller.first_time,
+        last_height: caller.last_height,
+        last_time: caller.last_time,
+        calls: caller.calls,
+        funds: caller.funds,
+        active,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
+    use cosmwasm_std::{coin, coins, from_binary, CosmosMsg};
 
     #[test]
     fn proper_initialization() {
         let mut deps = mock_dependencies(&[]);
 
-        let msg = InitMsg {};
+        let msg = InitMsg::default();
         let info = mock_info("creator", &coins(1000, "earth"));
 
         // we can just call .unwrap() to assert this was a success
@@ -80,18 +569,597 @@ mod tests {
     fn call() {
         let mut deps = mock_dependencies(&coins(2, "token"));
 
-        let msg = InitMsg {};
+        let msg = InitMsg::default();
         let info = mock_info("creator", &coins(2, "token"));
         let _res = init(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // beneficiary can release it
         let info = mock_info("anyone", &coins(2, "token"));
-        let msg = HandleMsg::Call {};
+        let msg = HandleMsg::Call { callback: None };
         let _res = handle(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // should increase counter by 1
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCallers {}).unwrap();
+        let msg = QueryMsg::GetCallers {
+            start_after: None,
+            limit: None,
+            status: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
         let value: QueryCallersResponse = from_binary(&res).unwrap();
         assert_eq!(1, value.callers.len());
     }
+
+    #[test]
+    fn paginated_callers() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("creator", &[]);
+        init(deps.as_mut(), mock_env(), info, InitMsg::default()).unwrap();
+
+        for caller in ["carol", "alice", "bob", "alice", "dave"].iter() {
+            let info = mock_info(*caller, &[]);
+            handle(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                HandleMsg::Call { callback: None },
+            )
+            .unwrap();
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCallerCount {}).unwrap();
+        let value: CallerCountResponse = from_binary(&res).unwrap();
+        assert_eq!(4, value.count);
+
+        let page = |start_after: Option<&str>| -> Vec<HumanAddr> {
+            let msg = QueryMsg::GetCallers {
+                start_after: start_after.map(HumanAddr::from),
+                limit: Some(3),
+                status: None,
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            from_binary::<QueryCallersResponse>(&res).unwrap().callers
+        };
+        let first = page(None);
+        assert_eq!(
+            vec![
+                HumanAddr::from("alice"),
+                HumanAddr::from("bob"),
+                HumanAddr::from("carol")
+            ],
+            first
+        );
+        assert_eq!(vec![HumanAddr::from("dave")], page(Some("carol")));
+        assert!(page(Some("dave")).is_empty());
+
+        for (address, expected) in [("bob", true), ("erin", false)].iter() {
+            let msg = QueryMsg::IsCaller {
+                address: HumanAddr::from(*address),
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let value: IsCallerResponse = from_binary(&res).unwrap();
+            assert_eq!(*expected, value.is_caller);
+        }
+    }
+
+    #[test]
+    fn caller_info() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("creator", &[]);
+        init(deps.as_mut(), mock_env(), info, InitMsg::default()).unwrap();
+
+        let mut env = mock_env();
+        let info = mock_info("alice", &coins(10, "ucosm"));
+        handle(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            HandleMsg::Call { callback: None },
+        )
+        .unwrap();
+        env.block.height += 5;
+        env.block.time += 30;
+        let info = mock_info("alice", &[coin(5, "ucosm"), coin(1, "uatom")]);
+        handle(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            HandleMsg::Call { callback: None },
+        )
+        .unwrap();
+
+        let msg = QueryMsg::GetCallerInfo {
+            address: "alice".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: CallerInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            CallerInfoResponse {
+                address: "alice".into(),
+                first_height: mock_env().block.height,
+                first_time: mock_env().block.time,
+                last_height: env.block.height,
+                last_time: env.block.time,
+                calls: 2,
+                funds: vec![coin(15, "ucosm"), coin(1, "uatom")],
+                active: true,
+            },
+            value
+        );
+        let msg = QueryMsg::GetCallerInfo {
+            address: "bob".into(),
+        };
+        query(deps.as_ref(), mock_env(), msg).unwrap_err();
+    }
+
+    #[test]
+    fn leaderboard() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("creator", &[]);
+        init(deps.as_mut(), mock_env(), info, InitMsg::default()).unwrap();
+
+        let calls = [
+            ("alice", 100, 50),
+            ("bob", 101, 0),
+            ("carol", 102, 70),
+            ("bob", 103, 0),
+            ("alice", 104, 30),
+            ("bob", 105, 10),
+        ];
+        for (caller, height, amount) in calls.iter() {
+            let mut env = mock_env();
+            env.block.height = *height;
+            let info = mock_info(*caller, &coins(*amount, "ucosm"));
+            handle(deps.as_mut(), env, info, HandleMsg::Call { callback: None }).unwrap();
+        }
+
+        let leaders = |order: LeaderboardOrder| -> Vec<(String, u64)> {
+            let msg = QueryMsg::GetLeaderboard {
+                order,
+                limit: Some(2),
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let value: LeaderboardResponse = from_binary(&res).unwrap();
+            value
+                .callers
+                .into_iter()
+                .map(|caller| (caller.address.to_string(), caller.calls))
+                .collect()
+        };
+        let expected = |leaders: &[(&str, u64)]| -> Vec<(String, u64)> {
+            leaders.iter().map(|(a, c)| (a.to_string(), *c)).collect()
+        };
+        assert_eq!(
+            expected(&[("bob", 3), ("alice", 2)]),
+            leaders(LeaderboardOrder::Calls {})
+        );
+        assert_eq!(
+            expected(&[("bob", 3), ("alice", 2)]),
+            leaders(LeaderboardOrder::Recent {})
+        );
+        assert_eq!(
+            expected(&[("alice", 2), ("bob", 3)]),
+            leaders(LeaderboardOrder::Earliest {})
+        );
+        assert_eq!(
+            expected(&[("alice", 2), ("carol", 1)]),
+            leaders(LeaderboardOrder::Funds {
+                denom: "ucosm".into()
+            })
+        );
+        assert!(leaders(LeaderboardOrder::Funds {
+            denom: "uatom".into()
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn allowlist_policy() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            policy: Some(Policy::Allowlist {}),
+            allowlist: Some(vec!["alice".into()]),
+            ..Default::default()
+        };
+        init(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let call = |deps: DepsMut, caller: &str| {
+            handle(
+                deps,
+                mock_env(),
+                mock_info(caller, &[]),
+                HandleMsg::Call { callback: None },
+            )
+        };
+        call(deps.as_mut(), "alice").unwrap();
+        let err = call(deps.as_mut(), "bob").unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let msg = HandleMsg::UpdateAllowlist {
+            add: vec!["bob".into()],
+            remove: vec!["alice".into()],
+        };
+        let err = handle(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        call(deps.as_mut(), "bob").unwrap();
+        let err = call(deps.as_mut(), "alice").unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let msg = QueryMsg::IsAllowed {
+            address: "bob".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: IsAllowedResponse = from_binary(&res).unwrap();
+        assert!(value.allowed);
+
+        // opening the contract lets anyone call
+        let msg = HandleMsg::UpdatePolicy {
+            policy: Policy::Open {},
+        };
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        call(deps.as_mut(), "carol").unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCallerCount {}).unwrap();
+        let value: CallerCountResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.count);
+    }
+
+    #[test]
+    fn fee_policy() {
+        let mut deps = mock_dependencies(&coins(30, "ucosm"));
+        let msg = InitMsg {
+            policy: Some(Policy::Fee {
+                fee: coin(0, "ucosm"),
+            }),
+            ..Default::default()
+        };
+        let err = init(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFee {}));
+        let msg = InitMsg {
+            policy: Some(Policy::Fee {
+                fee: coin(10, "ucosm"),
+            }),
+            ..Default::default()
+        };
+        init(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        for funds in [vec![], coins(9, "ucosm"), coins(10, "uatom")].iter() {
+            let info = mock_info("alice", funds);
+            let err = handle(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                HandleMsg::Call { callback: None },
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::InsufficientFee { .. }));
+        }
+        let info = mock_info("alice", &coins(10, "ucosm"));
+        handle(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            HandleMsg::Call { callback: None },
+        )
+        .unwrap();
+
+        let err = handle(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            HandleMsg::Withdraw {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let res = handle(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            HandleMsg::Withdraw {},
+        )
+        .unwrap();
+        assert_eq!(
+            vec![CosmosMsg::from(BankMsg::Send {
+                from_address: mock_env().contract.address,
+                to_address: "creator".into(),
+                amount: coins(30, "ucosm"),
+            })],
+            res.messages
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPolicy {}).unwrap();
+        let value: PolicyResponse = from_binary(&res).unwrap();
+        assert_eq!(HumanAddr::from("creator"), value.owner);
+        assert_eq!(
+            Policy::Fee {
+                fee: coin(10, "ucosm")
+            },
+            value.policy
+        );
+    }
+
+    #[test]
+    fn callback() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg::default();
+        init(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let call = |deps: DepsMut, caller: &str, callback| {
+            let msg = HandleMsg::Call { callback };
+            handle(deps, mock_env(), mock_info(caller, &[]), msg).unwrap()
+        };
+
+        // nothing is sent back without a hook
+        let res = call(deps.as_mut(), "alice", Some(true));
+        assert!(res.messages.is_empty());
+
+        let hook = Binary::from(br#"{"registered":{}}"#.to_vec());
+        let msg = HandleMsg::UpdateHook {
+            hook: Some(hook.clone()),
+        };
+        let err = handle(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = call(deps.as_mut(), "bob", None);
+        assert!(res.messages.is_empty());
+        let res = call(deps.as_mut(), "carol", Some(true));
+        assert_eq!(
+            vec![CosmosMsg::from(WasmMsg::Execute {
+                contract_addr: "carol".into(),
+                msg: hook,
+                send: vec![],
+            })],
+            res.messages
+        );
+        // callers are only notified when they are registered
+        let res = call(deps.as_mut(), "carol", Some(true));
+        assert!(res.messages.is_empty());
+    }
+
+    #[test]
+    fn ttl() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            ttl: Some(100),
+            ..Default::default()
+        };
+        init(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let start = mock_env().block.time;
+        let call = |deps: DepsMut, caller: &str, time: u64| {
+            let mut env = mock_env();
+            env.block.time = time;
+            let msg = HandleMsg::Call { callback: None };
+            handle(deps, env, mock_info(caller, &[]), msg).unwrap();
+        };
+        let callers = |deps: Deps, time: u64, status: Option<CallerStatus>| {
+            let mut env = mock_env();
+            env.block.time = time;
+            let msg = QueryMsg::GetCallers {
+                start_after: None,
+                limit: None,
+                status,
+            };
+            let value: QueryCallersResponse = from_binary(&query(deps, env, msg).unwrap()).unwrap();
+            value.callers
+        };
+
+        call(deps.as_mut(), "alice", start);
+        call(deps.as_mut(), "bob", start + 50);
+
+        let later = start + 120;
+        assert_eq!(
+            vec![HumanAddr::from("bob")],
+            callers(deps.as_ref(), later, Some(CallerStatus::Active))
+        );
+        assert_eq!(
+            vec![HumanAddr::from("alice")],
+            callers(deps.as_ref(), later, Some(CallerStatus::Inactive))
+        );
+        assert_eq!(2, callers(deps.as_ref(), later, None).len());
+
+        // a filtered page only reads limit callers and reports where to continue
+        let page = |start_after: Option<&str>| {
+            let mut env = mock_env();
+            env.block.time = later;
+            let msg = QueryMsg::GetCallers {
+                start_after: start_after.map(HumanAddr::from),
+                limit: Some(1),
+                status: Some(CallerStatus::Active),
+            };
+            let value: QueryCallersResponse =
+                from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+            (value.callers, value.next_start_after)
+        };
+        assert_eq!((vec![], Some(HumanAddr::from("alice"))), page(None));
+        assert_eq!(
+            (vec![HumanAddr::from("bob")], Some(HumanAddr::from("bob"))),
+            page(Some("alice"))
+        );
+        assert_eq!((vec![], None), page(Some("bob")));
+
+        let mut env = mock_env();
+        env.block.time = later;
+        let msg = QueryMsg::GetCallerInfo {
+            address: "alice".into(),
+        };
+        let value: CallerInfoResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert!(!value.active);
+
+        // calling again makes alice active
+        call(deps.as_mut(), "alice", later);
+        assert_eq!(
+            2,
+            callers(deps.as_ref(), later, Some(CallerStatus::Active)).len()
+        );
+
+        // only the owner can change the ttl, callers stay active without one
+        let msg = HandleMsg::UpdateTtl { ttl: None };
+        let err = handle(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTtl {}).unwrap();
+        let value: TtlResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.ttl);
+        assert!(callers(deps.as_ref(), start + 1000, Some(CallerStatus::Inactive)).is_empty());
+
+        // a ttl that reaches past the end of time keeps callers active
+        let msg = HandleMsg::UpdateTtl {
+            ttl: Some(u64::MAX),
+        };
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            2,
+            callers(deps.as_ref(), start + 1000, Some(CallerStatus::Active)).len()
+        );
+    }
+
+    #[test]
+    fn deregister() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg::default();
+        init(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        for caller in &["alice", "bob", "carol"] {
+            let info = mock_info(*caller, &coins(10, "ucosm"));
+            handle(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                HandleMsg::Call { callback: None },
+            )
+            .unwrap();
+        }
+
+        let info = mock_info("bob", &[]);
+        handle(deps.as_mut(), mock_env(), info, HandleMsg::Deregister {}).unwrap();
+        let info = mock_info("bob", &[]);
+        let err = handle(deps.as_mut(), mock_env(), info, HandleMsg::Deregister {}).unwrap_err();
+        assert!(matches!(err, ContractError::NotCaller { .. }));
+
+        // only the owner can remove callers
+        let msg = HandleMsg::RemoveCallers {
+            callers: vec!["carol".into()],
+        };
+        let err = handle(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        // addresses that never called can be blocked too
+        let msg = HandleMsg::RemoveCallers {
+            callers: vec!["dave".into(), "alice".into(), "erin".into()],
+        };
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("dave", &[]);
+        let err = handle(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            HandleMsg::Call { callback: None },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Removed { .. }));
+        let msg = HandleMsg::RestoreCallers {
+            callers: vec!["alice".into()],
+        };
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("alice", &coins(10, "ucosm"));
+        handle(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            HandleMsg::Call { callback: None },
+        )
+        .unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCallerCount {}).unwrap();
+        let value: CallerCountResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.count);
+        let msg = QueryMsg::IsCaller {
+            address: "bob".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: IsCallerResponse = from_binary(&res).unwrap();
+        assert!(!value.is_caller);
+
+        // removed callers leave the leaderboards
+        let orders = vec![
+            LeaderboardOrder::Calls {},
+            LeaderboardOrder::Recent {},
+            LeaderboardOrder::Earliest {},
+            LeaderboardOrder::Funds {
+                denom: "ucosm".into(),
+            },
+        ];
+        for order in orders {
+            let msg = QueryMsg::GetLeaderboard { order, limit: None };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let value: LeaderboardResponse = from_binary(&res).unwrap();
+            let addresses: Vec<_> = value.callers.into_iter().map(|c| c.address).collect();
+            assert_eq!(vec![HumanAddr::from("alice")], addresses);
+        }
+
+        // removed callers can only call again once the owner restores them
+        let info = mock_info("carol", &[]);
+        let err = handle(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            HandleMsg::Call { callback: None },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Removed { .. }));
+        let msg = QueryMsg::IsRemoved {
+            address: "carol".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: IsRemovedResponse = from_binary(&res).unwrap();
+        assert!(value.removed);
+        let msg = HandleMsg::RestoreCallers {
+            callers: vec!["carol".into()],
+        };
+        handle(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("carol", &[]);
+        handle(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            HandleMsg::Call { callback: None },
+        )
+        .unwrap();
+
+        // deregistered callers can call again
+        let info = mock_info("bob", &[]);
+        handle(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            HandleMsg::Call { callback: None },
+        )
+        .unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCallerCount {}).unwrap();
+        let value: CallerCountResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.count);
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{HumanAddr, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +8,18 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Calling needs a fee of {amount}{denom}")]
+    InsufficientFee { amount: Uint128, denom: String },
+
+    #[error("Fee must not be zero")]
+    InvalidFee {},
+
+    #[error("{address} is not a caller")]
+    NotCaller { address: HumanAddr },
+
+    #[error("{address} was removed from the callers")]
+    Removed { address: HumanAddr },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -1,26 +1,194 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::HumanAddr;
+use cosmwasm_std::{Binary, Coin, HumanAddr};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    // policy restricts who may call, anyone can call for free when unset
+    pub policy: Option<Policy>,
+    // allowlist holds the addresses allowed to call under the allowlist policy
+    pub allowlist: Option<Vec<HumanAddr>>,
+    // hook is the message sent back to new callers that ask for a callback
+    pub hook: Option<Binary>,
+    // ttl is the number of seconds after their last call callers become inactive,
+    // callers stay active when unset
+    pub ttl: Option<u64>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct InitMsg {}
+#[serde(rename_all = "snake_case")]
+pub enum Policy {
+    // Open lets anyone call for free
+    Open {},
+    // Allowlist only lets the listed addresses call
+    Allowlist {},
+    // Fee lets anyone call who sends at least fee along
+    Fee { fee: Coin },
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
-    Call {},
+    // Call registers the sender. A contract can set callback to be sent the hook message
+    // once it is registered, nothing is sent when no hook is configured
+    Call {
+        callback: Option<bool>,
+    },
+    // UpdatePolicy replaces the calling policy, only the owner can do it
+    UpdatePolicy {
+        policy: Policy,
+    },
+    // UpdateAllowlist changes the addresses allowed to call, only the owner can do it
+    UpdateAllowlist {
+        add: Vec<HumanAddr>,
+        remove: Vec<HumanAddr>,
+    },
+    // UpdateHook replaces the hook message, only the owner can do it
+    UpdateHook {
+        hook: Option<Binary>,
+    },
+    // Deregister removes the sender from the callers
+    Deregister {},
+    // RemoveCallers removes callers and keeps them from calling again until they are
+    // restored, addresses that are not callers are blocked from calling. Only the owner
+    // can do it
+    RemoveCallers {
+        callers: Vec<HumanAddr>,
+    },
+    // RestoreCallers lets removed callers call again, only the owner can do it
+    RestoreCallers {
+        callers: Vec<HumanAddr>,
+    },
+    // UpdateTtl replaces the ttl of callers, only the owner can do it
+    UpdateTtl {
+        ttl: Option<u64>,
+    },
+    // Withdraw sends the balance of the contract, collected fees included, to the owner.
+    // Only the owner can do it
+    Withdraw {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    // GetCount returns the current count as a json-encoded number
-    GetCallers {},
+    // GetCallers reads up to limit callers after start_after, in address order.
+    // status only returns those of them that are active or inactive
+    GetCallers {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+        status: Option<CallerStatus>,
+    },
+    // IsCaller returns whether address has called the contract
+    IsCaller {
+        address: HumanAddr,
+    },
+    // GetCallerCount returns the number of callers
+    GetCallerCount {},
+    // GetPolicy returns the owner and the calling policy
+    GetPolicy {},
+    // GetHook returns the message sent back to new callers that ask for a callback
+    GetHook {},
+    // GetTtl returns the number of seconds after their last call callers become inactive
+    GetTtl {},
+    // IsAllowed returns whether address is on the allowlist
+    IsAllowed {
+        address: HumanAddr,
+    },
+    // IsRemoved returns whether the owner removed address from the callers
+    IsRemoved {
+        address: HumanAddr,
+    },
+    // GetCallerInfo returns the calls recorded for address
+    GetCallerInfo {
+        address: HumanAddr,
+    },
+    // GetLeaderboard returns the first callers in the given order
+    GetLeaderboard {
+        order: LeaderboardOrder,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CallerStatus {
+    // Active callers called within the ttl
+    Active,
+    Inactive,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardOrder {
+    // Calls puts the callers with the most calls first
+    Calls {},
+    // Recent puts the callers that called last first
+    Recent {},
+    // Earliest puts the callers that called first first
+    Earliest {},
+    // Funds puts the callers that sent the most of denom first
+    Funds { denom: String },
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct QueryCallersResponse {
     pub callers: Vec<HumanAddr>,
+    // next_start_after is the last caller read, set while more callers may follow.
+    // With a status, fewer than limit callers can be returned before the end.
+    pub next_start_after: Option<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsCallerResponse {
+    pub is_caller: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CallerCountResponse {
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CallerInfoResponse {
+    pub address: HumanAddr,
+    pub first_height: u64,
+    pub first_time: u64,
+    pub last_height: u64,
+    pub last_time: u64,
+    pub calls: u64,
+    pub funds: Vec<Coin>,
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardResponse {
+    pub callers: Vec<CallerInfoResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PolicyResponse {
+    pub owner: HumanAddr,
+    pub policy: Policy,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsAllowedResponse {
+    pub allowed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsRemovedResponse {
+    pub removed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HookResponse {
+    pub hook: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TtlResponse {
+    pub ttl: Option<u64>,
 }
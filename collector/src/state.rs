@@ -1,20 +1,110 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Storage};
-use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
+use cosmwasm_std::{Binary, Coin, Empty, HumanAddr, Storage};
+use cosmwasm_storage::{
+    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
+    Singleton,
+};
+
+use crate::msg::{LeaderboardOrder, Policy};
 
 pub static CONFIG_KEY: &[u8] = b"config";
+pub static CALLERS_KEY: &[u8] = b"callers";
+pub static INDEX_KEY: &[u8] = b"index";
+pub static CONTRACT_INFO_KEY: &[u8] = b"contract_info";
+pub static ALLOWLIST_KEY: &[u8] = b"allowlist";
+pub static REMOVED_KEY: &[u8] = b"removed";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
-    pub callers: Vec<HumanAddr>,
+    // count is the number of addresses in the callers bucket
+    pub count: u64,
+    pub owner: HumanAddr,
+    pub policy: Policy,
+    pub hook: Option<Binary>,
+    pub ttl: Option<u64>,
+}
+
+// ContractVersion identifies the contract owning the storage, for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractVersion {
+    pub contract: String,
+    pub version: String,
 }
 
-pub fn config(storage: &mut dyn Storage) -> Singleton<State> {
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CallerInfo {
+    pub first_height: u64,
+    pub first_time: u64,
+    pub last_height: u64,
+    pub last_time: u64,
+    pub calls: u64,
+    // funds holds the total of each denom sent along the calls
+    pub funds: Vec<Coin>,
+}
+
+pub fn config(storage: &mut dyn Storage) -> Singleton<'_, State> {
     singleton(storage, CONFIG_KEY)
 }
 
-pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<State> {
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, State> {
     singleton_read(storage, CONFIG_KEY)
 }
+
+pub fn contract_info(storage: &mut dyn Storage) -> Singleton<'_, ContractVersion> {
+    singleton(storage, CONTRACT_INFO_KEY)
+}
+
+pub fn contract_info_read(storage: &dyn Storage) -> ReadonlySingleton<'_, ContractVersion> {
+    singleton_read(storage, CONTRACT_INFO_KEY)
+}
+
+// callers is keyed by the address of every caller
+pub fn callers(storage: &mut dyn Storage) -> Bucket<'_, CallerInfo> {
+    bucket(storage, CALLERS_KEY)
+}
+
+pub fn callers_read(storage: &dyn Storage) -> ReadonlyBucket<'_, CallerInfo> {
+    bucket_read(storage, CALLERS_KEY)
+}
+
+// allowlist is keyed by the addresses allowed to call
+pub fn allowlist(storage: &mut dyn Storage) -> Bucket<'_, Empty> {
+    bucket(storage, ALLOWLIST_KEY)
+}
+
+pub fn allowlist_read(storage: &dyn Storage) -> ReadonlyBucket<'_, Empty> {
+    bucket_read(storage, ALLOWLIST_KEY)
+}
+
+// removed is keyed by the callers the owner removed, they may not call until restored
+pub fn removed(storage: &mut dyn Storage) -> Bucket<'_, Empty> {
+    bucket(storage, REMOVED_KEY)
+}
+
+pub fn removed_read(storage: &dyn Storage) -> ReadonlyBucket<'_, Empty> {
+    bucket_read(storage, REMOVED_KEY)
+}
+
+// index sorts the callers for a leaderboard, its keys are the big endian value
+// callers are sorted by followed by the address of the caller
+pub fn index<'a>(storage: &'a mut dyn Storage, order: &LeaderboardOrder) -> Bucket<'a, Empty> {
+    Bucket::multilevel(storage, &index_namespaces(order))
+}
+
+pub fn index_read<'a>(
+    storage: &'a dyn Storage,
+    order: &LeaderboardOrder,
+) -> ReadonlyBucket<'a, Empty> {
+    ReadonlyBucket::multilevel(storage, &index_namespaces(order))
+}
+
+fn index_namespaces(order: &LeaderboardOrder) -> Vec<&[u8]> {
+    match order {
+        LeaderboardOrder::Calls {} => vec![INDEX_KEY, b"calls"],
+        LeaderboardOrder::Recent {} => vec![INDEX_KEY, b"recent"],
+        LeaderboardOrder::Earliest {} => vec![INDEX_KEY, b"earliest"],
+        LeaderboardOrder::Funds { denom } => vec![INDEX_KEY, b"funds", denom.as_bytes()],
+    }
+}
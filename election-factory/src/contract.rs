@@ -0,0 +1,491 @@
+use std::convert::TryInto;
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, Binary, ContractResult, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response,
+    StdError, StdResult, SubMsg, SubMsgExecutionResponse, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw_storage_plus::Bound;
+use election::msg::VoteResponse;
+
+use crate::error::ContractError;
+use crate::msg::{ElectionInfo, ElectionsResponse, ExecuteMsg, InstantiateMsg, Phase, QueryMsg};
+use crate::state::{Config, Election, PendingElection, CONFIG, ELECTIONS, ELECTION_COUNT, PENDING};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:election-factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const INSTANTIATE_REPLY_ID: u64 = 1;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let config = Config {
+        owner: info.sender.clone(),
+        election_code_id: msg.election_code_id,
+    };
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CONFIG.save(deps.storage, &config)?;
+    ELECTION_COUNT.save(deps.storage, &0)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreateElection { label, election } => {
+            try_create_election(deps, env, info, label, election)
+        }
+        ExecuteMsg::UpdateConfig {
+            owner,
+            election_code_id,
+        } => try_update_config(deps, info, owner, election_code_id),
+    }
+}
+
+pub fn try_create_election(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    label: String,
+    mut election: Box<election::msg::InstantiateMsg>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // the factory instantiates the election, so the creator has to be made its owner
+    if election.owner.is_none() {
+        election.owner = Some(info.sender.to_string());
+    }
+
+    PENDING.save(
+        deps.storage,
+        &PendingElection {
+            creator: info.sender.clone(),
+            label: label.clone(),
+        },
+    )?;
+    let msg = WasmMsg::Instantiate {
+        admin: Some(info.sender.into()),
+        code_id: config.election_code_id,
+        msg: to_binary(&election)?,
+        funds: vec![],
+        label,
+    };
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(msg, INSTANTIATE_REPLY_ID))
+        .add_attribute("method", "create_election")
+        .add_attribute("code_id", config.election_code_id.to_string()))
+}
+
+pub fn try_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    election_code_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_validate(&owner)?;
+    }
+    if let Some(election_code_id) = election_code_id {
+        config.election_code_id = election_code_id;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("method", "update_config"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != INSTANTIATE_REPLY_ID {
+        return Err(ContractError::UnknownReply { id: msg.id });
+    }
+    let res = match msg.result {
+        ContractResult::Ok(res) => res,
+        ContractResult::Err(err) => return Err(StdError::generic_err(err).into()),
+    };
+    let address = deps.api.addr_validate(&contract_address(&res)?)?;
+    let pending = PENDING.load(deps.storage)?;
+    PENDING.remove(deps.storage);
+
+    let id = ELECTION_COUNT.load(deps.storage)?;
+    ELECTIONS.save(
+        deps.storage,
+        id.into(),
+        &Election {
+            address: address.clone(),
+            creator: pending.creator,
+            label: pending.label,
+        },
+    )?;
+    ELECTION_COUNT.save(deps.storage, &(id + 1))?;
+
+    Ok(Response::new()
+        .add_attribute("method", "register_election")
+        .add_attribute("id", id.to_string())
+        .add_attribute("election", address))
+}
+
+// contract_address reads the address of a new contract from its instantiate event
+fn contract_address(res: &SubMsgExecutionResponse) -> Result<String, ContractError> {
+    res.events
+        .iter()
+        .filter(|event| event.ty == "instantiate")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == "_contract_address")
+        .map(|attr| attr.value.clone())
+        .ok_or(ContractError::MissingAddress {})
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetConfig {} => to_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::ListElections {
+            phase,
+            start_after,
+            limit,
+        } => to_binary(&query_elections(deps, phase, start_after, limit)?),
+    }
+}
+
+// query_elections asks every election for its status, so changes of the voting window
+// made by the election owner are taken into account.
+// At most limit elections are asked, elections that fail to answer are left out.
+fn query_elections(
+    deps: Deps,
+    phase: Option<Phase>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ElectionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    let mut elections = vec![];
+    let mut scanned = 0;
+    let mut last = None;
+    for item in ELECTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+    {
+        let (key, election) = item?;
+        let id = key
+            .as_slice()
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| StdError::invalid_data_size(8, key.len()))?;
+        scanned += 1;
+        last = Some(id);
+        let info: VoteResponse = match deps
+            .querier
+            .query_wasm_smart(&election.address, &election::msg::QueryMsg::GetVoteInfo {})
+        {
+            Ok(info) => info,
+            Err(_) => continue,
+        };
+        if matches!(&phase, Some(phase) if !phase.includes(&info.status)) {
+            continue;
+        }
+        elections.push(ElectionInfo {
+            id,
+            address: election.address,
+            creator: election.creator,
+            label: election.label,
+            status: info.status,
+        });
+    }
+    Ok(ElectionsResponse {
+        elections,
+        next_start_after: if scanned == limit { last } else { None },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        from_binary, from_slice, Addr, CosmosMsg, Empty, Event, OwnedDeps, Querier, QuerierResult,
+        QueryRequest, SystemError, SystemResult, WasmQuery,
+    };
+    use election::msg::{Scheduled, Status};
+    use std::collections::HashMap;
+
+    // ElectionQuerier answers vote info queries of elections with a fixed status
+    struct ElectionQuerier {
+        base: MockQuerier,
+        statuses: HashMap<String, Status>,
+    }
+
+    impl Querier for ElectionQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match request {
+                QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                    match from_binary(&msg).unwrap() {
+                        election::msg::QueryMsg::GetVoteInfo {} => {}
+                        _ => panic!("unexpected election query"),
+                    }
+                    // elections without a status fail to answer
+                    let status = match self.statuses.get(&contract_addr) {
+                        Some(status) => status.clone(),
+                        None => {
+                            return SystemResult::Err(SystemError::NoSuchContract {
+                                addr: contract_addr,
+                            })
+                        }
+                    };
+                    let res = VoteResponse {
+                        status,
+                        start: Scheduled::AtHeight(10_000),
+                        end: Scheduled::AtHeight(20_000),
+                        votes: vec![],
+                        unrevealed: 0,
+                    };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                _ => self.base.raw_query(bin_request),
+            }
+        }
+    }
+
+    fn mock_deps() -> OwnedDeps<MockStorage, MockApi, ElectionQuerier> {
+        OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: ElectionQuerier {
+                base: MockQuerier::new(&[]),
+                statuses: HashMap::new(),
+            },
+        }
+    }
+
+    fn election_msg() -> Box<election::msg::InstantiateMsg> {
+        Box::new(election::msg::InstantiateMsg {
+            start: Scheduled::AtHeight(10_000),
+            end: Scheduled::AtHeight(20_000),
+            candidates: vec!["alice".into(), "bob".into()],
+            quorum: None,
+            threshold: None,
+            weight: None,
+            mode: None,
+            seats: None,
+            registry: None,
+            reveal_end: None,
+            owner: None,
+            actions: None,
+            nomination: None,
+        })
+    }
+
+    // add_election goes through the whole creation of an election at address
+    fn add_election(mut deps: DepsMut, creator: &str, address: &str) {
+        let msg = ExecuteMsg::CreateElection {
+            label: format!("{} election", creator),
+            election: election_msg(),
+        };
+        execute(deps.branch(), mock_env(), mock_info(creator, &[]), msg).unwrap();
+        reply(deps, mock_env(), instantiate_reply(address)).unwrap();
+    }
+
+    fn instantiate_reply(address: &str) -> Reply {
+        let event = Event::new("instantiate").add_attribute("_contract_address", address);
+        Reply {
+            id: INSTANTIATE_REPLY_ID,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![event],
+                data: None,
+            }),
+        }
+    }
+
+    fn list(deps: Deps, phase: Option<Phase>, start_after: Option<u64>) -> (Vec<u64>, Option<u64>) {
+        let msg = QueryMsg::ListElections {
+            phase,
+            start_after,
+            limit: Some(2),
+        };
+        let res = query(deps, mock_env(), msg).unwrap();
+        let value: ElectionsResponse = from_binary(&res).unwrap();
+        let ids = value.elections.into_iter().map(|e| e.id).collect();
+        (ids, value.next_start_after)
+    }
+
+    #[test]
+    fn create_election() {
+        let mut deps = mock_deps();
+        let msg = InstantiateMsg {
+            election_code_id: 7,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateElection {
+            label: "board".into(),
+            election: election_msg(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(INSTANTIATE_REPLY_ID, res.messages[0].id);
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin,
+                code_id,
+                msg,
+                label,
+                ..
+            }) => {
+                assert_eq!(&Some("alice".to_string()), admin);
+                assert_eq!(7, *code_id);
+                assert_eq!("board", label);
+                // the creator owns the election rather than the factory
+                let msg: election::msg::InstantiateMsg = from_binary(msg).unwrap();
+                assert_eq!(Some("alice".to_string()), msg.owner);
+            }
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+
+        let err = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: 2,
+                ..instantiate_reply("e")
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UnknownReply { id: 2 }));
+        let reply_msg = Reply {
+            id: INSTANTIATE_REPLY_ID,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        let err = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+        assert!(matches!(err, ContractError::MissingAddress {}));
+
+        reply(deps.as_mut(), mock_env(), instantiate_reply("election0")).unwrap();
+        deps.querier
+            .statuses
+            .insert("election0".into(), Status::Upcoming);
+        let msg = QueryMsg::ListElections {
+            phase: None,
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: ElectionsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            vec![ElectionInfo {
+                id: 0,
+                address: Addr::unchecked("election0"),
+                creator: Addr::unchecked("alice"),
+                label: "board".into(),
+                status: Status::Upcoming,
+            }],
+            value.elections
+        );
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_deps();
+        let msg = InstantiateMsg {
+            election_code_id: 7,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig {
+            owner: None,
+            election_code_id: Some(8),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let value: Config = from_binary(&res).unwrap();
+        assert_eq!(8, value.election_code_id);
+        assert_eq!(Addr::unchecked("creator"), value.owner);
+    }
+
+    #[test]
+    fn list_elections() {
+        let mut deps = mock_deps();
+        let msg = InstantiateMsg {
+            election_code_id: 7,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let statuses = [
+            Status::Open,
+            Status::Upcoming,
+            Status::Ended,
+            Status::Revealing,
+            Status::Cancelled,
+            Status::Upcoming,
+        ];
+        for (i, status) in statuses.iter().enumerate() {
+            let address = format!("election{}", i);
+            add_election(deps.as_mut(), "alice", &address);
+            deps.querier.statuses.insert(address, status.clone());
+        }
+
+        // election6 fails to answer
+        add_election(deps.as_mut(), "alice", "election6");
+
+        assert_eq!((vec![0, 1], Some(1)), list(deps.as_ref(), None, None));
+        assert_eq!((vec![2, 3], Some(3)), list(deps.as_ref(), None, Some(1)));
+        // a phase only keeps matching elections of the limit elections read
+        let upcoming = Some(Phase::Upcoming);
+        assert_eq!(
+            (vec![1], Some(1)),
+            list(deps.as_ref(), upcoming.clone(), None)
+        );
+        assert_eq!(
+            (vec![], Some(3)),
+            list(deps.as_ref(), upcoming.clone(), Some(1))
+        );
+        assert_eq!(
+            (vec![5], Some(5)),
+            list(deps.as_ref(), upcoming.clone(), Some(3))
+        );
+        assert_eq!((vec![], None), list(deps.as_ref(), upcoming, Some(5)));
+        assert_eq!(
+            (vec![0], Some(1)),
+            list(deps.as_ref(), Some(Phase::Active), None)
+        );
+        assert_eq!(
+            (vec![4], Some(5)),
+            list(deps.as_ref(), Some(Phase::Finished), Some(3))
+        );
+        assert_eq!((vec![], None), list(deps.as_ref(), None, Some(5)));
+    }
+}
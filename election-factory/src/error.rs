@@ -0,0 +1,17 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Unknown reply id {id}")]
+    UnknownReply { id: u64 },
+
+    #[error("Election instantiation did not report a contract address")]
+    MissingAddress {},
+}
@@ -0,0 +1,76 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Addr;
+use election::msg::Status;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub election_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    // CreateElection instantiates a new election, the sender owns it unless election.owner is set
+    CreateElection {
+        label: String,
+        election: Box<election::msg::InstantiateMsg>,
+    },
+    // UpdateConfig changes the owner or the code of new elections, only the owner can do it
+    UpdateConfig {
+        owner: Option<String>,
+        election_code_id: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    GetConfig {},
+    // ListElections reads up to limit elections created after start_after, in order of
+    // creation. phase only keeps those of them currently in that phase, elections that
+    // fail to report their status are left out
+    ListElections {
+        phase: Option<Phase>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Upcoming,
+    // Active elections accept ballots or reveals
+    Active,
+    // Finished elections have ended or were cancelled
+    Finished,
+}
+
+impl Phase {
+    pub fn includes(&self, status: &Status) -> bool {
+        match self {
+            Phase::Upcoming => *status == Status::Upcoming,
+            Phase::Active => matches!(status, Status::Open | Status::Revealing),
+            Phase::Finished => matches!(status, Status::Ended | Status::Cancelled),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ElectionsResponse {
+    pub elections: Vec<ElectionInfo>,
+    // next_start_after is the id of the last election read, set while more elections
+    // may follow. With a phase, fewer than limit elections can be returned before the end.
+    pub next_start_after: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ElectionInfo {
+    pub id: u64,
+    pub address: Addr,
+    pub creator: Addr,
+    pub label: String,
+    pub status: Status,
+}
@@ -0,0 +1,36 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map, U64Key};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: Addr,
+    // election_code_id is the code instantiated for new elections
+    pub election_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Election {
+    pub address: Addr,
+    pub creator: Addr,
+    pub label: String,
+}
+
+// PendingElection is an election waiting for its instantiation reply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingElection {
+    pub creator: Addr,
+    pub label: String,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+// ELECTION_COUNT holds the number of elections created so far, it is the id of the next one
+pub const ELECTION_COUNT: Item<u64> = Item::new("election_count");
+
+pub const PENDING: Item<PendingElection> = Item::new("pending");
+
+// ELECTIONS holds every created election by id, in order of creation
+pub const ELECTIONS: Map<U64Key, Election> = Map::new("elections");
@@ -3,7 +3,11 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use election::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, VoteResponse};
+use election::msg::{
+    ActionsResponse, BallotResponse, BallotsResponse, DelegationResponse, EligibilityResponse,
+    ExecuteMsg, HasVotedResponse, InstantiateMsg, MigrateMsg, NominationResponse, QueryMsg,
+    RunoffResponse, TurnoutResponse, VoteResponse, VotingPowerResponse, WinnerResponse,
+};
 use election::state::State;
 
 fn main() {
@@ -15,6 +19,18 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(VoteResponse), &out_dir);
+    export_schema(&schema_for!(WinnerResponse), &out_dir);
+    export_schema(&schema_for!(RunoffResponse), &out_dir);
+    export_schema(&schema_for!(EligibilityResponse), &out_dir);
+    export_schema(&schema_for!(TurnoutResponse), &out_dir);
+    export_schema(&schema_for!(DelegationResponse), &out_dir);
+    export_schema(&schema_for!(ActionsResponse), &out_dir);
+    export_schema(&schema_for!(VotingPowerResponse), &out_dir);
+    export_schema(&schema_for!(BallotResponse), &out_dir);
+    export_schema(&schema_for!(BallotsResponse), &out_dir);
+    export_schema(&schema_for!(HasVotedResponse), &out_dir);
+    export_schema(&schema_for!(NominationResponse), &out_dir);
 }
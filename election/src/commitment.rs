@@ -0,0 +1,14 @@
+use sha2::{Digest, Sha256};
+
+// ballot_commitment returns the hex encoded sha256 hash a secret ballot is committed with.
+// Every candidate is followed by a zero byte so that different ballots cannot collide,
+// and the salt keeps the ballot from being guessed from its hash.
+pub fn ballot_commitment(candidates: &[String], salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    for candidate in candidates {
+        hasher.update(candidate.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher.update(salt.as_bytes());
+    hex::encode(hasher.finalize())
+}
@@ -1,163 +1,3607 @@
-#[cfg(not(feature = "library"))]
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
 
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    to_binary, Addr, Api, BankMsg, Binary, BlockInfo, Coin, Decimal, Deps, DepsMut, Empty, Env,
+    Event, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw4::{Cw4QueryMsg, MemberResponse};
+use cw_storage_plus::Bound;
+use semver::Version;
 
+use crate::commitment::ballot_commitment;
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, Vote, VoteResponse};
-use crate::state::{State, VoteInfo, STATE};
+use crate::merkle::{validate_root, verify_proof};
+use crate::msg::{
+    ActionsResponse, BallotInfo, BallotResponse, BallotsResponse, DelegationResponse,
+    EligibilityResponse, ExecuteMsg, HasVotedResponse, InstantiateMsg, MigrateMsg,
+    NominationResponse, Outcome, QueryMsg, Quorum, Registry, RunoffResponse, RunoffRound,
+    Scheduled, SnapshotPower, Status, Threshold, TurnoutResponse, Vote, VoteResponse, VotingMode,
+    VotingPowerResponse, WeightSource, WinnerResponse,
+};
+use crate::runoff::{instant_runoff, Runoff};
+use crate::state::{
+    Ballot, Commitment, Counting, Delegation, Nomination, State, Totals, VoteInfo, VoterRegistry,
+    WeightSourceV2, ACTIONS, BALLOTS, BALLOT_COUNT, COMMITMENTS, COUNTING, DELEGATED, DELEGATIONS,
+    DEPOSITS, LEGACY_STATE, RESULT, SNAPSHOT, STATE, STATE_V2, STATUS, TALLY, TOTALS, UNREVEALED,
+    VOTERS,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:election";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_COUNT_LIMIT: u32 = 100;
+const MAX_COUNT_LIMIT: u32 = 500;
+
 // Note, you can use StdResult in some functions where you do not
 // make use of the custom errors
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    let weight = match msg.weight {
+        Some(WeightSource::Cw4 { address }) => Some(WeightSource::Cw4 {
+            address: deps.api.addr_validate(&address)?.into(),
+        }),
+        weight => weight,
+    };
+    validate_window(&msg.start, &msg.end, msg.reveal_end.as_ref(), &env.block)?;
+    validate_snapshot_height(weight.as_ref(), &msg.start)?;
+    if msg.candidates.is_empty() && msg.nomination.is_none() {
+        return Err(ContractError::NoCandidates {});
+    }
+    validate_candidates(&msg.candidates)?;
+    let actions = msg.actions.unwrap_or_default();
+    for (i, action) in actions.iter().enumerate() {
+        if !msg.candidates.contains(&action.candidate) {
+            return Err(ContractError::UnknownCandidate {
+                candidate: action.candidate.clone(),
+            });
+        }
+        if actions[..i].iter().any(|a| a.candidate == action.candidate) {
+            return Err(ContractError::DuplicateCandidate {
+                candidate: action.candidate.clone(),
+            });
+        }
+    }
+    let mode = msg.mode.unwrap_or_default();
+    let seats = msg.seats.unwrap_or(1);
+    // nominees may still fill the seats when there is a nomination phase
+    if seats == 0 || (msg.nomination.is_none() && seats as usize > msg.candidates.len()) {
+        return Err(ContractError::InvalidSeats {
+            seats,
+            candidates: msg.candidates.len(),
+        });
+    }
+    if mode == VotingMode::RankedChoice && seats > 1 {
+        return Err(ContractError::RankedChoiceSeats {});
+    }
+
+    let registry = match msg.registry {
+        None => None,
+        Some(Registry::Voters { voters }) => Some(VoterRegistry::Listed {
+            voters: add_voters(deps.storage, deps.api, &voters)?,
+        }),
+        Some(Registry::Merkle { root, voters }) => {
+            validate_root(&root)?;
+            Some(VoterRegistry::Merkle { root, voters })
+        }
+    };
+    if let Some(Quorum::Turnout { ratio }) = &msg.quorum {
+        if registry.is_none() {
+            return Err(ContractError::TurnoutNeedsRegistry {});
+        }
+        if *ratio > Decimal::one() {
+            return Err(ContractError::InvalidQuorum {});
+        }
+    }
+    if let Some(threshold) = msg.threshold {
+        if !threshold.is_met(Decimal::one()) {
+            return Err(ContractError::InvalidThreshold {});
+        }
+    }
+
+    let nomination = match msg.nomination {
+        None => None,
+        Some(nomination) => {
+            if nomination.deposit.amount.is_zero() {
+                return Err(ContractError::InvalidDeposit {
+                    deposit: nomination.deposit.to_string(),
+                });
+            }
+            if nomination.min_share > Decimal::one() {
+                return Err(ContractError::InvalidMinShare {});
+            }
+            Some(Nomination {
+                deposit: nomination.deposit,
+                min_share: nomination.min_share,
+                treasury: deps.api.addr_validate(&nomination.treasury)?,
+                settled: false,
+            })
+        }
+    };
+
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
     let state = State {
+        owner: owner.clone(),
         start: msg.start,
         end: msg.end,
         candidates: msg.candidates,
-        votes: Vec::new(),
+        quorum: msg.quorum,
+        threshold: msg.threshold,
+        weight,
+        mode,
+        seats,
+        registry,
+        reveal_end: msg.reveal_end,
+        cancelled: false,
+        executed: false,
+        nomination,
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    STATUS.save(deps.storage, &state.status(&env.block))?;
+    BALLOT_COUNT.save(deps.storage, &0)?;
+    UNREVEALED.save(deps.storage, &0)?;
+    for action in actions {
+        ACTIONS.save(deps.storage, &action.candidate, &action.msgs)?;
+    }
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("owner", info.sender))
+        .add_attribute("owner", owner))
 }
 
 // And declare a custom Error variant for the ones where you will want to make use of it
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    match msg {
-        ExecuteMsg::Vote { candidate } => try_vote(deps, env, info, candidate),
+    let events = status_events(deps.branch(), &env)?;
+    let mut res = match msg {
+        ExecuteMsg::Vote { candidates, proof } => try_vote(deps, env, info, candidates, proof),
+        ExecuteMsg::Commit { commitment, proof } => try_commit(deps, env, info, commitment, proof),
+        ExecuteMsg::Reveal { candidates, salt } => try_reveal(deps, env, info, candidates, salt),
+        ExecuteMsg::Delegate { delegate, proof } => try_delegate(deps, env, info, delegate, proof),
+        ExecuteMsg::Undelegate {} => try_undelegate(deps, env, info),
+        ExecuteMsg::UpdateCandidates { add, remove } => {
+            try_update_candidates(deps, env, info, add, remove)
+        }
+        ExecuteMsg::UpdateWindow { start, end } => try_update_window(deps, env, info, start, end),
+        ExecuteMsg::Cancel {} => try_cancel(deps, env, info),
+        ExecuteMsg::UpdateVoters { add, remove } => try_update_voters(deps, env, info, add, remove),
+        ExecuteMsg::UpdateMerkleRoot { root, voters } => {
+            try_update_merkle_root(deps, env, info, root, voters)
+        }
+        ExecuteMsg::SubmitSnapshot { powers } => try_submit_snapshot(deps, env, info, powers),
+        ExecuteMsg::Count { limit } => try_count(deps, env, limit),
+        ExecuteMsg::Execute {} => try_execute(deps, env),
+        ExecuteMsg::Nominate {} => try_nominate(deps, env, info),
+        ExecuteMsg::Finalize {} => try_finalize(deps, env),
+    }?;
+    res.events.splice(0..0, events);
+    Ok(res)
+}
+
+// status_events reports the status changes that happened since the last executed message.
+// Opening and closing depend on the block, so they are only noticed by the next message.
+// Cancelling is reported by Cancel, and finalizing by the Count storing the result.
+fn status_events(deps: DepsMut, env: &Env) -> StdResult<Vec<Event>> {
+    let status = STATE.load(deps.storage)?.status(&env.block);
+    let previous = STATUS.may_load(deps.storage)?;
+    STATUS.save(deps.storage, &status)?;
+    let previous = match previous {
+        Some(previous) => previous,
+        // migrated elections start reporting from their current status
+        None => return Ok(vec![]),
+    };
+
+    let stage = |status: &Status| match status {
+        Status::Upcoming => Some(0),
+        Status::Open => Some(1),
+        Status::Revealing | Status::Ended => Some(2),
+        Status::Cancelled => None,
+    };
+    let mut events = vec![];
+    if let (Some(from), Some(to)) = (stage(&previous), stage(&status)) {
+        if from < 1 && to >= 1 {
+            events.push(election_event("opened"));
+        }
+        if from < 2 && to >= 2 {
+            events.push(election_event("closed"));
+        }
     }
+    Ok(events)
+}
+
+fn election_event(action: &str) -> Event {
+    Event::new("election").add_attribute("action", action)
 }
 
 pub fn try_vote(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    candidate: String,
+    candidates: Vec<String>,
+    proof: Option<Vec<String>>,
 ) -> Result<Response, ContractError> {
-    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        if env.block.height < state.start || env.block.height > state.end {
-            return Err(ContractError::NotAllowance {
-                begin: state.start,
-                end: state.end,
-            });
-        }
-        state.votes.push(VoteInfo {
+    let state = STATE.load(deps.storage)?;
+    if state.reveal_end.is_some() {
+        return Err(ContractError::SecretBallot {});
+    }
+    assert_voting_open(&state, &env)?;
+    validate_ballot(&state, &candidates)?;
+    // voting directly overrides a delegation
+    remove_delegation(deps.storage, &state, &info.sender)?;
+    let weight = ballot_weight(deps.as_ref(), &state, &info.sender, proof.as_deref())?;
+
+    record_ballot(
+        deps.storage,
+        &state,
+        &info.sender,
+        Ballot {
+            candidates: candidates.clone(),
+            weight,
+        },
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "vote")
+        .add_attribute("voter", info.sender)
+        .add_attribute("candidate", candidates.join(","))
+        .add_attribute("weight", weight))
+}
+
+pub fn try_commit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    commitment: String,
+    proof: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if state.reveal_end.is_none() {
+        return Err(ContractError::PublicBallot {});
+    }
+    assert_voting_open(&state, &env)?;
+    if COMMITMENTS.has(deps.storage, &info.sender) {
+        return Err(ContractError::AlreadyVoted {
+            voter: info.sender.into(),
+        });
+    }
+    let weight = ballot_weight(deps.as_ref(), &state, &info.sender, proof.as_deref())?;
+
+    COMMITMENTS.save(
+        deps.storage,
+        &info.sender,
+        &Commitment {
+            hash: commitment.to_lowercase(),
+            weight,
+        },
+    )?;
+    UNREVEALED.update(deps.storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+    Ok(Response::new().add_attribute("method", "commit"))
+}
+
+pub fn try_reveal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    candidates: Vec<String>,
+    salt: String,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let reveal_end = state.reveal_end.ok_or(ContractError::PublicBallot {})?;
+    if state.cancelled {
+        return Err(ContractError::Cancelled {});
+    }
+    if !state.end.is_passed(&env.block) || reveal_end.is_passed(&env.block) {
+        return Err(ContractError::NotRevealPeriod {
+            end: state.end,
+            reveal_end,
+        });
+    }
+    let commitment = COMMITMENTS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or_else(|| ContractError::NoCommitment {
             voter: info.sender.to_string(),
-            candidate: candidate,
+        })?;
+    if ballot_commitment(&candidates, &salt) != commitment.hash {
+        return Err(ContractError::CommitmentMismatch {});
+    }
+    validate_ballot(&state, &candidates)?;
+
+    COMMITMENTS.remove(deps.storage, &info.sender);
+    UNREVEALED.update(deps.storage, |count| -> StdResult<_> { Ok(count - 1) })?;
+    let ballot = Ballot {
+        candidates: candidates.clone(),
+        weight: commitment.weight,
+    };
+    record_ballot(deps.storage, &state, &info.sender, ballot)?;
+    Ok(Response::new()
+        .add_attribute("action", "vote")
+        .add_attribute("method", "reveal")
+        .add_attribute("voter", info.sender)
+        .add_attribute("candidate", candidates.join(","))
+        .add_attribute("weight", commitment.weight))
+}
+
+pub fn try_delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegate: String,
+    proof: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if state.reveal_end.is_some() {
+        return Err(ContractError::SecretBallot {});
+    }
+    assert_voting_open(&state, &env)?;
+    let delegate = deps.api.addr_validate(&delegate)?;
+    if delegate == info.sender {
+        return Err(ContractError::SelfDelegation {});
+    }
+    if DELEGATIONS.has(deps.storage, &delegate) || DELEGATED.has(deps.storage, &info.sender) {
+        return Err(ContractError::DelegationChain {});
+    }
+    if !is_eligible(deps.as_ref(), &state, &info.sender, proof.as_deref())? {
+        return Err(ContractError::NotEligible {
+            voter: info.sender.into(),
         });
-        Ok(state)
+    }
+    let weight = voting_power(deps.as_ref(), &state, &info.sender)?;
+    if weight.is_zero() {
+        return Err(ContractError::NoVotingPower {
+            voter: info.sender.into(),
+        });
+    }
+
+    remove_delegation(deps.storage, &state, &info.sender)?;
+    withdraw_ballot(deps.storage, &state, &info.sender)?;
+    DELEGATIONS.save(
+        deps.storage,
+        &info.sender,
+        &Delegation {
+            delegate: delegate.clone(),
+            weight,
+        },
+    )?;
+    DELEGATED.update(deps.storage, &delegate, |delegated| -> StdResult<_> {
+        Ok(delegated.unwrap_or_default() + weight)
     })?;
-    Ok(Response::default())
+    if let Some(mut ballot) = BALLOTS.may_load(deps.storage, &delegate)? {
+        add_tally(deps.storage, &state, &ballot.candidates, weight)?;
+        ballot.weight += weight;
+        BALLOTS.save(deps.storage, &delegate, &ballot)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "delegate")
+        .add_attribute("delegate", delegate))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetVoteInfo {} => to_binary(&query_vote_info(deps)?),
+pub fn try_undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    assert_voting_open(&state, &env)?;
+    if !remove_delegation(deps.storage, &state, &info.sender)? {
+        return Err(ContractError::NotDelegated {
+            voter: info.sender.into(),
+        });
     }
+
+    Ok(Response::new().add_attribute("method", "undelegate"))
 }
 
-fn query_vote_info(deps: Deps) -> StdResult<VoteResponse> {
-    let state = STATE.load(deps.storage)?;
-    let mut vote_info = HashMap::new();
-    for vote in state.votes {
-        let count = vote_info.entry(vote.candidate).or_insert(0);
-        *count += 1;
+// remove_delegation takes the delegated voting power of delegator away from its delegate
+// and returns whether there was a delegation
+fn remove_delegation(
+    storage: &mut dyn Storage,
+    state: &State,
+    delegator: &Addr,
+) -> StdResult<bool> {
+    let delegation = match DELEGATIONS.may_load(storage, delegator)? {
+        Some(delegation) => delegation,
+        None => return Ok(false),
+    };
+    DELEGATIONS.remove(storage, delegator);
+
+    let delegated = DELEGATED.load(storage, &delegation.delegate)? - delegation.weight;
+    if delegated.is_zero() {
+        DELEGATED.remove(storage, &delegation.delegate);
+    } else {
+        DELEGATED.save(storage, &delegation.delegate, &delegated)?;
+    }
+    if let Some(mut ballot) = BALLOTS.may_load(storage, &delegation.delegate)? {
+        sub_tally(storage, state, &ballot.candidates, delegation.weight)?;
+        ballot.weight -= delegation.weight;
+        BALLOTS.save(storage, &delegation.delegate, &ballot)?;
     }
+    Ok(true)
+}
 
-    let mut votes = Vec::new();
-    for (candidate, count) in vote_info {
-        votes.push(Vote {
-            candidate: candidate,
-            count: count,
+fn assert_voting_open(state: &State, env: &Env) -> Result<(), ContractError> {
+    if state.cancelled {
+        return Err(ContractError::Cancelled {});
+    }
+    if !state.start.is_reached(&env.block) || state.end.is_passed(&env.block) {
+        return Err(ContractError::NotAllowance {
+            begin: state.start,
+            end: state.end,
         });
     }
-    Ok(VoteResponse {
-        votes: votes,
-        start: state.start,
-        end: state.end,
-    })
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
+// ballot_weight checks that voter may cast a ballot and returns its weight,
+// including the voting power delegated to voter
+fn ballot_weight(
+    deps: Deps,
+    state: &State,
+    voter: &Addr,
+    proof: Option<&[String]>,
+) -> Result<Uint128, ContractError> {
+    if !is_eligible(deps, state, voter, proof)? {
+        return Err(ContractError::NotEligible {
+            voter: voter.into(),
+        });
+    }
+    let weight = voting_power(deps, state, voter)?
+        + DELEGATED.may_load(deps.storage, voter)?.unwrap_or_default();
+    if weight.is_zero() {
+        return Err(ContractError::NoVotingPower {
+            voter: voter.into(),
+        });
+    }
+    Ok(weight)
+}
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies(&[]);
+// record_ballot stores an open ballot, replacing the previous ballot of voter,
+// and adds it to the tally
+fn record_ballot(
+    storage: &mut dyn Storage,
+    state: &State,
+    voter: &Addr,
+    ballot: Ballot,
+) -> StdResult<()> {
+    withdraw_ballot(storage, state, voter)?;
+    add_tally(storage, state, &ballot.candidates, ballot.weight)?;
+    BALLOTS.save(storage, voter, &ballot)?;
+    BALLOT_COUNT.update(storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+    Ok(())
+}
 
-        let msg = InstantiateMsg {
-            start: 10,
-            end: 100,
-            candidates: Vec::new(),
-        };
-        let info = mock_info("creator", &coins(1000, "earth"));
+// withdraw_ballot removes the ballot of voter, if any, and takes it out of the tally
+fn withdraw_ballot(storage: &mut dyn Storage, state: &State, voter: &Addr) -> StdResult<()> {
+    if let Some(ballot) = BALLOTS.may_load(storage, voter)? {
+        sub_tally(storage, state, &ballot.candidates, ballot.weight)?;
+        BALLOTS.remove(storage, voter);
+        BALLOT_COUNT.update(storage, |count| -> StdResult<_> { Ok(count - 1) })?;
+    }
+    Ok(())
+}
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+fn add_tally(
+    storage: &mut dyn Storage,
+    state: &State,
+    candidates: &[String],
+    weight: Uint128,
+) -> StdResult<()> {
+    for candidate in tallied(&state.mode, candidates) {
+        TALLY.update(storage, candidate, |count| -> StdResult<_> {
+            Ok(count.unwrap_or_default() + weight)
+        })?;
+    }
+    let mut totals = TOTALS.may_load(storage)?.unwrap_or_default();
+    totals.weight += weight;
+    if candidates.is_empty() {
+        totals.abstained += weight;
+    }
+    TOTALS.save(storage, &totals)
+}
 
-        // it worked, let's query the state
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
-        let value: VoteResponse = from_binary(&res).unwrap();
-        assert_eq!(10, value.start);
-        assert_eq!(100, value.end);
+fn sub_tally(
+    storage: &mut dyn Storage,
+    state: &State,
+    candidates: &[String],
+    weight: Uint128,
+) -> StdResult<()> {
+    for candidate in tallied(&state.mode, candidates) {
+        TALLY.update(storage, candidate, |count| -> StdResult<_> {
+            Ok(count.unwrap_or_default().checked_sub(weight)?)
+        })?;
     }
+    let mut totals = TOTALS.may_load(storage)?.unwrap_or_default();
+    totals.weight = totals.weight.checked_sub(weight)?;
+    if candidates.is_empty() {
+        totals.abstained = totals.abstained.checked_sub(weight)?;
+    }
+    TOTALS.save(storage, &totals)
+}
 
-    #[test]
-    fn vote() {
-        let mut deps = mock_dependencies(&coins(2, "token"));
+pub fn try_update_candidates(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    assert_owner_before_start(&state, &env, &info)?;
 
-        let mut candidates: Vec<String> = Vec::new();
-        candidates.push("candidates1".into());
-        candidates.push("candidates2".into());
-        let msg = InstantiateMsg {
-            start: 10_000,
-            end: 20_000,
-            candidates: Vec::new(),
-        };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let mut refunds = vec![];
+    for candidate in remove {
+        let i = state
+            .candidates
+            .iter()
+            .position(|c| *c == candidate)
+            .ok_or(ContractError::UnknownCandidate { candidate })?;
+        let candidate = state.candidates.remove(i);
+        ACTIONS.remove(deps.storage, &candidate);
+        // removed nominees get their deposit back
+        if let Some(nomination) = &state.nomination {
+            let nominee = Addr::unchecked(candidate);
+            if let Some(amount) = DEPOSITS.may_load(deps.storage, &nominee)? {
+                DEPOSITS.remove(deps.storage, &nominee);
+                refunds.push(refund(&nominee, amount, nomination));
+            }
+        }
+    }
+    for candidate in add {
+        if state.candidates.contains(&candidate) {
+            return Err(ContractError::CandidateExists { candidate });
+        }
+        state.candidates.push(candidate);
+    }
+    if state.nomination.is_none() && state.seats as usize > state.candidates.len() {
+        return Err(ContractError::InvalidSeats {
+            seats: state.seats,
+            candidates: state.candidates.len(),
+        });
+    }
+    STATE.save(deps.storage, &state)?;
 
-        // beneficiary can release it
-        let info = mock_info("voter1", &coins(2, "token"));
-        let msg = ExecuteMsg::Vote {
-            candidate: "candidates1".into(),
-        };
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    Ok(Response::new()
+        .add_messages(refunds)
+        .add_attribute("method", "update_candidates"))
+}
 
-        // should increase counter by 1
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
-        let value: VoteResponse = from_binary(&res).unwrap();
-        assert_eq!(10_000, value.start);
-        assert_eq!(20_000, value.end);
-        assert_eq!("candidates1", value.votes[0].candidate);
-        assert_eq!(1, value.votes[0].count);
+pub fn try_update_window(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start: Scheduled,
+    end: Scheduled,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    assert_owner_before_start(&state, &env, &info)?;
+    validate_window(&start, &end, state.reveal_end.as_ref(), &env.block)?;
+    validate_snapshot_height(state.weight.as_ref(), &start)?;
+
+    state.start = start;
+    state.end = end;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_window")
+        .add_attribute("start", start.to_string())
+        .add_attribute("end", end.to_string()))
+}
+
+fn validate_window(
+    start: &Scheduled,
+    end: &Scheduled,
+    reveal_end: Option<&Scheduled>,
+    block: &BlockInfo,
+) -> Result<(), ContractError> {
+    let closes_at = reveal_end.unwrap_or(end);
+    if start.partial_cmp(end).is_none() || closes_at.partial_cmp(end).is_none() {
+        return Err(ContractError::MixedWindow {});
+    }
+    if start >= end {
+        return Err(ContractError::InvalidWindow {});
+    }
+    if matches!(reveal_end, Some(reveal_end) if reveal_end <= end) {
+        return Err(ContractError::InvalidRevealEnd {});
+    }
+    if end.is_reached(block) {
+        return Err(ContractError::WindowInPast {});
+    }
+    Ok(())
+}
+
+// validate_snapshot_height checks that a cw4 group can be queried at the start of voting,
+// which is only possible at a height
+fn validate_snapshot_height(
+    weight: Option<&WeightSource>,
+    start: &Scheduled,
+) -> Result<(), ContractError> {
+    match (weight, start) {
+        (Some(WeightSource::Cw4 { .. }), Scheduled::AtTime(_)) => {
+            Err(ContractError::SnapshotHeight {})
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_candidates(candidates: &[String]) -> Result<(), ContractError> {
+    for (i, candidate) in candidates.iter().enumerate() {
+        if candidates[..i].contains(candidate) {
+            return Err(ContractError::CandidateExists {
+                candidate: candidate.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn try_cancel(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        if info.sender != state.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+        if state.cancelled {
+            return Err(ContractError::Cancelled {});
+        }
+        // the results of an ended election stand, deposits are settled by them
+        if state.end.is_passed(&env.block) {
+            return Err(ContractError::VotingEnded {});
+        }
+        state.cancelled = true;
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_event(election_event("cancelled"))
+        .add_attribute("method", "cancel"))
+}
+
+pub fn try_update_voters(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    assert_owner_before_start(&state, &env, &info)?;
+    let voters = match state.registry {
+        Some(VoterRegistry::Listed { voters }) => voters,
+        _ => return Err(ContractError::RegistryMismatch {}),
+    };
+
+    let added = add_voters(deps.storage, deps.api, &add)?;
+    let mut removed = 0;
+    for voter in remove {
+        let voter = deps.api.addr_validate(&voter)?;
+        if VOTERS.has(deps.storage, &voter) {
+            VOTERS.remove(deps.storage, &voter);
+            removed += 1;
+        }
+    }
+    state.registry = Some(VoterRegistry::Listed {
+        voters: voters + added - removed,
+    });
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attribute("method", "update_voters"))
+}
+
+pub fn try_update_merkle_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    root: String,
+    voters: u64,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    assert_owner_before_start(&state, &env, &info)?;
+    if !matches!(state.registry, Some(VoterRegistry::Merkle { .. })) {
+        return Err(ContractError::RegistryMismatch {});
+    }
+
+    validate_root(&root)?;
+    state.registry = Some(VoterRegistry::Merkle { root, voters });
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attribute("method", "update_merkle_root"))
+}
+
+pub fn try_submit_snapshot(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    powers: Vec<SnapshotPower>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    assert_owner_before_start(&state, &env, &info)?;
+    if state.weight != Some(WeightSource::Snapshot {}) {
+        return Err(ContractError::WeightMismatch {});
+    }
+
+    for SnapshotPower { address, power } in &powers {
+        let address = deps.api.addr_validate(address)?;
+        if power.is_zero() {
+            SNAPSHOT.remove(deps.storage, &address);
+        } else {
+            SNAPSHOT.save(deps.storage, &address, power)?;
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "submit_snapshot")
+        .add_attribute("powers", powers.len().to_string()))
+}
+
+pub fn try_count(deps: DepsMut, env: Env, limit: Option<u32>) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if state.cancelled {
+        return Err(ContractError::Cancelled {});
+    }
+    assert_ended(&state, &env)?;
+    if RESULT.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::AlreadyCounted {});
+    }
+    if state.mode != VotingMode::RankedChoice {
+        // the running tallies hold the result already
+        let votes = sorted_votes(deps.as_ref(), &state)?;
+        RESULT.save(deps.storage, &votes)?;
+        return Ok(Response::new()
+            .add_event(election_event("finalized"))
+            .add_attribute("method", "count")
+            .add_attribute("finished", "true"));
+    }
+
+    let mut counting = match COUNTING.may_load(deps.storage)? {
+        Some(counting) => counting,
+        None => Counting {
+            runoff: Runoff::new(&state.candidates),
+            counted_to: None,
+        },
+    };
+    let limit = limit.unwrap_or(DEFAULT_COUNT_LIMIT).min(MAX_COUNT_LIMIT) as usize;
+    let mut budget = limit;
+    let mut finished = false;
+    while budget > 0 {
+        let start = counting
+            .counted_to
+            .as_ref()
+            .map(|voter| Bound::exclusive(voter.as_str()));
+        let ballots = BALLOTS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(budget)
+            .collect::<StdResult<Vec<_>>>()?;
+        budget -= ballots.len();
+        for (voter, ballot) in ballots {
+            // abstentions take no part in the runoff
+            if !ballot.candidates.is_empty() {
+                counting.runoff.count(&ballot);
+            }
+            let voter = String::from_utf8(voter).map_err(StdError::from)?;
+            counting.counted_to = Some(Addr::unchecked(voter));
+        }
+        // the round may go on past the ballots taken
+        if budget == 0 {
+            break;
+        }
+        counting.counted_to = None;
+        if counting.runoff.end_round() {
+            finished = true;
+            break;
+        }
+    }
+    COUNTING.save(deps.storage, &counting)?;
+    let mut res = Response::new();
+    if finished {
+        let votes = counting
+            .runoff
+            .rounds
+            .last()
+            .map(|round| round.votes.clone())
+            .unwrap_or_default();
+        RESULT.save(deps.storage, &votes)?;
+        res = res.add_event(election_event("finalized"));
+    }
+
+    Ok(res
+        .add_attribute("method", "count")
+        .add_attribute("ballots", (limit - budget).to_string())
+        .add_attribute("finished", finished.to_string()))
+}
+
+pub fn try_execute(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if state.executed {
+        return Err(ContractError::AlreadyExecuted {});
+    }
+    if RESULT.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NotCounted {});
+    }
+    // the stored result is read back, rather than counted again
+    let winners = match query_winner(deps.as_ref(), env)?.outcome {
+        Outcome::Elected { winners } => winners,
+        _ => return Err(ContractError::NoWinner {}),
+    };
+
+    let mut msgs = vec![];
+    for winner in &winners {
+        msgs.extend(
+            ACTIONS
+                .may_load(deps.storage, &winner.candidate)?
+                .unwrap_or_default(),
+        );
+    }
+    state.executed = true;
+    STATE.save(deps.storage, &state)?;
+
+    let winners: Vec<_> = winners.into_iter().map(|w| w.candidate).collect();
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "execute")
+        .add_attribute("winners", winners.join(",")))
+}
+
+pub fn try_nominate(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let nomination = state
+        .nomination
+        .as_ref()
+        .ok_or(ContractError::NoNomination {})?;
+    if state.cancelled {
+        return Err(ContractError::Cancelled {});
+    }
+    if state.start.is_reached(&env.block) {
+        return Err(ContractError::VotingStarted {});
+    }
+    if info.funds != [nomination.deposit.clone()] {
+        return Err(ContractError::InvalidDeposit {
+            deposit: nomination.deposit.to_string(),
+        });
+    }
+    let candidate = info.sender.to_string();
+    if state.candidates.contains(&candidate) {
+        return Err(ContractError::CandidateExists { candidate });
+    }
+
+    DEPOSITS.save(deps.storage, &info.sender, &nomination.deposit.amount)?;
+    state.candidates.push(candidate.clone());
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "nominate")
+        .add_attribute("candidate", candidate))
+}
+
+// try_finalize settles the deposits by the share of the weight cast for candidates.
+// Ranked choice nominees are judged by their first preferences.
+pub fn try_finalize(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let mut nomination = state
+        .nomination
+        .take()
+        .ok_or(ContractError::NoNomination {})?;
+    if nomination.settled {
+        return Err(ContractError::AlreadySettled {});
+    }
+    if !state.cancelled {
+        assert_ended(&state, &env)?;
+    }
+
+    let deposits = DEPOSITS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (nominee, amount) = item?;
+            Ok((Addr::unchecked(String::from_utf8(nominee)?), amount))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let totals = TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    let cast = totals.weight - totals.abstained;
+    let mut refunds = vec![];
+    let mut forfeited = Uint128::zero();
+    for (nominee, amount) in deposits {
+        let count = TALLY
+            .may_load(deps.storage, nominee.as_str())?
+            .unwrap_or_default();
+        let share = if cast.is_zero() {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(count, cast)
+        };
+        if state.cancelled || share >= nomination.min_share {
+            refunds.push(refund(&nominee, amount, &nomination));
+        } else {
+            forfeited += amount;
+        }
+        DEPOSITS.remove(deps.storage, &nominee);
+    }
+    if !forfeited.is_zero() {
+        refunds.push(refund(&nomination.treasury, forfeited, &nomination));
+    }
+    nomination.settled = true;
+    state.nomination = Some(nomination);
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_messages(refunds)
+        .add_attribute("method", "finalize")
+        .add_attribute("forfeited", forfeited))
+}
+
+// refund pays amount of the deposit denom to recipient
+fn refund(recipient: &Addr, amount: Uint128, nomination: &Nomination) -> BankMsg {
+    BankMsg::Send {
+        to_address: recipient.into(),
+        amount: vec![Coin {
+            denom: nomination.deposit.denom.clone(),
+            amount,
+        }],
+    }
+}
+
+fn assert_owner_before_start(
+    state: &State,
+    env: &Env,
+    info: &MessageInfo,
+) -> Result<(), ContractError> {
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if state.cancelled {
+        return Err(ContractError::Cancelled {});
+    }
+    if state.start.is_reached(&env.block) {
+        return Err(ContractError::VotingStarted {});
+    }
+    Ok(())
+}
+
+// add_voters lists the given voters and returns how many of them were not listed yet
+fn add_voters(storage: &mut dyn Storage, api: &dyn Api, voters: &[String]) -> StdResult<u64> {
+    let mut added = 0;
+    for voter in voters {
+        let voter = api.addr_validate(voter)?;
+        if !VOTERS.has(storage, &voter) {
+            VOTERS.save(storage, &voter, &Empty {})?;
+            added += 1;
+        }
+    }
+    Ok(added)
+}
+
+fn is_eligible(
+    deps: Deps,
+    state: &State,
+    voter: &Addr,
+    proof: Option<&[String]>,
+) -> Result<bool, ContractError> {
+    match (&state.registry, proof) {
+        (None, _) => Ok(true),
+        (Some(VoterRegistry::Listed { .. }), _) => Ok(VOTERS.has(deps.storage, voter)),
+        (Some(VoterRegistry::Merkle { root, .. }), Some(proof)) => {
+            verify_proof(root, voter.as_str(), proof)
+        }
+        (Some(VoterRegistry::Merkle { .. }), None) => Ok(false),
+    }
+}
+
+// tallied returns the candidates of a ballot that count towards TALLY, none when it abstains
+fn tallied<'a>(mode: &VotingMode, candidates: &'a [String]) -> &'a [String] {
+    match mode {
+        VotingMode::Plurality | VotingMode::RankedChoice => &candidates[..candidates.len().min(1)],
+        VotingMode::Approval => candidates,
+    }
+}
+
+fn validate_ballot(state: &State, candidates: &[String]) -> Result<(), ContractError> {
+    if state.mode == VotingMode::Plurality && candidates.len() > 1 {
+        return Err(ContractError::TooManyCandidates { max: 1 });
+    }
+    for (i, candidate) in candidates.iter().enumerate() {
+        if !state.candidates.contains(candidate) {
+            return Err(ContractError::UnknownCandidate {
+                candidate: candidate.clone(),
+            });
+        }
+        if candidates[..i].contains(candidate) {
+            return Err(ContractError::DuplicateCandidate {
+                candidate: candidate.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+// voting_power returns the weight of a ballot cast by voter.
+// Weights are fixed at the start of voting, so moving funds around while voting is open
+// does not change them.
+fn voting_power(deps: Deps, state: &State, voter: &Addr) -> StdResult<Uint128> {
+    match &state.weight {
+        None => Ok(Uint128::new(1)),
+        Some(WeightSource::Snapshot {}) => {
+            Ok(SNAPSHOT.may_load(deps.storage, voter)?.unwrap_or_default())
+        }
+        Some(WeightSource::Cw4 { address }) => {
+            let at_height = match state.start {
+                Scheduled::AtHeight(height) => Some(height),
+                Scheduled::AtTime(_) => None,
+            };
+            let res: MemberResponse = deps.querier.query_wasm_smart(
+                address,
+                &Cw4QueryMsg::Member {
+                    addr: voter.into(),
+                    at_height,
+                },
+            )?;
+            Ok(res.weight.unwrap_or_default().into())
+        }
+    }
+}
+
+// migrate upgrades an election stored by an older version of this contract
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            contract: stored.contract,
+        });
+    }
+    let version = Version::parse(&stored.version)?;
+    if version > Version::parse(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.into(),
+        });
+    }
+
+    let owner = msg
+        .owner
+        .map(|owner| deps.api.addr_validate(&owner))
+        .transpose()?;
+    if version < Version::new(0, 2, 0) {
+        let owner = owner.ok_or_else(|| ContractError::MissingOwner {
+            version: stored.version.clone(),
+        })?;
+        migrate_legacy_state(deps.branch(), owner)?;
+    } else {
+        if version < Version::new(0, 3, 0) {
+            migrate_v2_state(deps.storage)?;
+        }
+        if let Some(owner) = owner {
+            STATE.update(deps.storage, |mut state| -> StdResult<_> {
+                state.owner = owner;
+                Ok(state)
+            })?;
+        }
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+// migrate_legacy_state converts the 0.1.0 layout, which kept every vote in STATE.
+// The last vote of each voter becomes their ballot, unless it names someone who was not
+// a candidate. Elections without candidates took votes for any name, the names voted
+// for become their candidates.
+fn migrate_legacy_state(deps: DepsMut, owner: Addr) -> StdResult<()> {
+    let legacy = LEGACY_STATE.load(deps.storage)?;
+    let mut last_votes: BTreeMap<String, String> = BTreeMap::new();
+    for VoteInfo { voter, candidate } in legacy.votes {
+        last_votes.insert(voter, candidate);
+    }
+    let names = if legacy.candidates.is_empty() {
+        last_votes.values().cloned().collect()
+    } else {
+        legacy.candidates
+    };
+    let mut seen = BTreeSet::new();
+    let candidates: Vec<String> = names
+        .into_iter()
+        .filter(|candidate| seen.insert(candidate.clone()))
+        .collect();
+    let state = State {
+        owner,
+        start: Scheduled::AtHeight(legacy.start),
+        end: Scheduled::AtHeight(legacy.end),
+        candidates,
+        quorum: None,
+        threshold: None,
+        weight: None,
+        mode: VotingMode::Plurality,
+        seats: 1,
+        registry: None,
+        reveal_end: None,
+        cancelled: false,
+        executed: false,
+        nomination: None,
+    };
+    STATE.save(deps.storage, &state)?;
+    BALLOT_COUNT.save(deps.storage, &0)?;
+    UNREVEALED.save(deps.storage, &0)?;
+
+    let valid: BTreeSet<&String> = state.candidates.iter().collect();
+    for (voter, candidate) in &last_votes {
+        if !valid.contains(candidate) {
+            continue;
+        }
+        let voter = deps.api.addr_validate(voter)?;
+        let ballot = Ballot {
+            candidates: vec![candidate.clone()],
+            weight: Uint128::new(1),
+        };
+        record_ballot(deps.storage, &state, &voter, ballot)?;
+    }
+    Ok(())
+}
+
+// migrate_v2_state converts the 0.2 layout, whose threshold was met by reaching it.
+// Elections weighed by live balances can not be carried over, as no voting power was
+// fixed at their start.
+fn migrate_v2_state(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let v2 = STATE_V2.load(storage)?;
+    let weight = match v2.weight {
+        None => None,
+        Some(WeightSourceV2::Snapshot {}) => Some(WeightSource::Snapshot {}),
+        Some(WeightSourceV2::Cw4 { address }) => Some(WeightSource::Cw4 { address }),
+        Some(_) => return Err(ContractError::LiveWeight {}),
+    };
+    let state = State {
+        owner: v2.owner,
+        start: v2.start,
+        end: v2.end,
+        candidates: v2.candidates,
+        quorum: v2.quorum,
+        threshold: v2.threshold.map(|ratio| Threshold::AtLeast { ratio }),
+        weight,
+        mode: v2.mode,
+        seats: v2.seats,
+        registry: v2.registry,
+        reveal_end: v2.reveal_end,
+        cancelled: v2.cancelled,
+        executed: v2.executed,
+        nomination: v2.nomination,
+    };
+    STATE.save(storage, &state)?;
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetVoteInfo {} => to_binary(&query_vote_info(deps, env)?),
+        QueryMsg::GetWinner {} => to_binary(&query_winner(deps, env)?),
+        QueryMsg::GetRunoff {} => to_binary(&query_runoff(deps, env)?),
+        QueryMsg::IsEligible { voter, proof } => to_binary(&query_eligible(deps, voter, proof)?),
+        QueryMsg::GetTurnout {} => to_binary(&query_turnout(deps)?),
+        QueryMsg::GetDelegation { voter } => to_binary(&query_delegation(deps, voter)?),
+        QueryMsg::GetVotingPower { voter } => to_binary(&query_voting_power(deps, voter)?),
+        QueryMsg::GetActions { candidate } => to_binary(&query_actions(deps, candidate)?),
+        QueryMsg::GetBallot { voter } => to_binary(&query_ballot(deps, voter)?),
+        QueryMsg::ListBallots { start_after, limit } => {
+            to_binary(&query_ballots(deps, start_after, limit)?)
+        }
+        QueryMsg::HasVoted { voter } => to_binary(&query_has_voted(deps, voter)?),
+        QueryMsg::GetNomination {} => to_binary(&query_nomination(deps)?),
+    }
+}
+
+fn query_nomination(deps: Deps) -> StdResult<NominationResponse> {
+    let nomination = STATE
+        .load(deps.storage)?
+        .nomination
+        .ok_or_else(|| StdError::generic_err("Election has no nomination phase"))?;
+    let nominees = DEPOSITS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(String::from_utf8)
+        .collect::<Result<_, _>>()?;
+    Ok(NominationResponse {
+        deposit: nomination.deposit,
+        min_share: nomination.min_share,
+        treasury: nomination.treasury.into(),
+        nominees,
+        settled: nomination.settled,
+    })
+}
+
+fn query_ballot(deps: Deps, voter: String) -> StdResult<BallotResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let ballot = BALLOTS.may_load(deps.storage, &voter)?;
+    Ok(BallotResponse {
+        ballot: ballot.map(|ballot| BallotInfo {
+            voter: voter.into(),
+            candidates: ballot.candidates,
+            weight: ballot.weight,
+        }),
+    })
+}
+
+fn query_ballots(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<BallotsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let ballots = BALLOTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (voter, ballot) = item?;
+            Ok(BallotInfo {
+                voter: String::from_utf8(voter)?,
+                candidates: ballot.candidates,
+                weight: ballot.weight,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(BallotsResponse { ballots })
+}
+
+fn query_has_voted(deps: Deps, voter: String) -> StdResult<HasVotedResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    Ok(HasVotedResponse {
+        voted: BALLOTS.has(deps.storage, &voter) || COMMITMENTS.has(deps.storage, &voter),
+    })
+}
+
+fn query_voting_power(deps: Deps, voter: String) -> StdResult<VotingPowerResponse> {
+    let state = STATE.load(deps.storage)?;
+    let voter = deps.api.addr_validate(&voter)?;
+    Ok(VotingPowerResponse {
+        power: voting_power(deps, &state, &voter)?,
+    })
+}
+
+fn query_actions(deps: Deps, candidate: String) -> StdResult<ActionsResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(ActionsResponse {
+        msgs: ACTIONS
+            .may_load(deps.storage, &candidate)?
+            .unwrap_or_default(),
+        executed: state.executed,
+    })
+}
+
+fn query_delegation(deps: Deps, voter: String) -> StdResult<DelegationResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let delegation = DELEGATIONS.may_load(deps.storage, &voter)?;
+    Ok(DelegationResponse {
+        delegate: delegation.as_ref().map(|d| d.delegate.to_string()),
+        weight: delegation.map(|d| d.weight).unwrap_or_default(),
+        delegated: DELEGATED
+            .may_load(deps.storage, &voter)?
+            .unwrap_or_default(),
+    })
+}
+
+fn query_vote_info(deps: Deps, env: Env) -> StdResult<VoteResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(VoteResponse {
+        status: state.status(&env.block),
+        votes: sorted_votes(deps, &state)?,
+        start: state.start,
+        end: state.end,
+        unrevealed: UNREVEALED.load(deps.storage)?,
+    })
+}
+
+fn query_winner(deps: Deps, env: Env) -> StdResult<WinnerResponse> {
+    let state = STATE.load(deps.storage)?;
+    let ballots = BALLOT_COUNT.load(deps.storage)?;
+    let totals = TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    let mut res = WinnerResponse {
+        ballots,
+        unrevealed: UNREVEALED.load(deps.storage)?,
+        weight: totals.weight,
+        abstained: totals.abstained,
+        quorum_met: None,
+        threshold_met: None,
+        outcome: Outcome::Cancelled {},
+        counted: false,
+    };
+    if state.cancelled {
+        return Ok(res);
+    }
+    assert_ended(&state, &env)?;
+
+    let result = RESULT.may_load(deps.storage)?;
+    res.counted = result.is_some();
+    let votes = match result {
+        Some(votes) => votes,
+        None => final_votes(deps, &state)?,
+    };
+    let seats = state.seats as usize;
+    res.quorum_met = state
+        .quorum
+        .as_ref()
+        .map(|quorum| quorum_met(quorum, &state, ballots, &totals));
+    res.threshold_met = state
+        .threshold
+        .map(|threshold| threshold_met(threshold, &votes[..seats.min(votes.len())], &totals));
+    res.outcome = match (&state.quorum, state.threshold) {
+        (Some(quorum), _) if res.quorum_met == Some(false) => Outcome::QuorumNotMet {
+            quorum: quorum.clone(),
+        },
+        (_, Some(threshold)) if res.threshold_met == Some(false) => {
+            Outcome::ThresholdNotMet { threshold }
+        }
+        _ => elect(votes, seats),
+    };
+    Ok(res)
+}
+
+fn quorum_met(quorum: &Quorum, state: &State, ballots: u64, totals: &Totals) -> bool {
+    match quorum {
+        Quorum::Ballots { count } => ballots >= *count,
+        Quorum::Weight { amount } => totals.weight >= *amount,
+        Quorum::Turnout { ratio } => {
+            let registered = state.registry.as_ref().map_or(0, |r| r.voters());
+            Decimal::from_ratio(ballots, registered.max(1)) >= *ratio
+        }
+    }
+}
+
+// threshold_met checks that every leading candidate meets the threshold of the weight
+// cast for candidates
+fn threshold_met(threshold: Threshold, leading: &[Vote], totals: &Totals) -> bool {
+    let cast = totals.weight - totals.abstained;
+    !cast.is_zero()
+        && leading
+            .iter()
+            .all(|vote| threshold.is_met(Decimal::from_ratio(vote.count, cast)))
+}
+
+// final_votes returns the votes the seats are filled from, a ranked choice
+// election is decided by the last round of its runoff
+fn final_votes(deps: Deps, state: &State) -> StdResult<Vec<Vote>> {
+    match state.mode {
+        VotingMode::Plurality | VotingMode::Approval => sorted_votes(deps, state),
+        VotingMode::RankedChoice => Ok(runoff(deps, state)?
+            .pop()
+            .map(|round| round.votes)
+            .unwrap_or_default()),
+    }
+}
+
+// elect fills the seats from votes sorted highest first. When candidates are tied
+// for the last seats and cannot all be elected, the outcome is a tie.
+fn elect(mut votes: Vec<Vote>, seats: usize) -> Outcome {
+    let cutoff = match votes.get(seats.saturating_sub(1)) {
+        Some(vote) => vote.count,
+        None => return Outcome::Elected { winners: votes },
+    };
+    let contenders = votes.iter().take_while(|vote| vote.count >= cutoff).count();
+    if contenders <= seats {
+        votes.truncate(seats);
+        return Outcome::Elected { winners: votes };
+    }
+
+    votes.truncate(contenders);
+    let elected = votes.iter().take_while(|vote| vote.count > cutoff).count();
+    let tied = votes.split_off(elected);
+    Outcome::Tie {
+        elected: votes,
+        tied,
+    }
+}
+
+fn query_runoff(deps: Deps, env: Env) -> StdResult<RunoffResponse> {
+    let state = STATE.load(deps.storage)?;
+    assert_ended(&state, &env)?;
+    if state.mode != VotingMode::RankedChoice {
+        return Err(StdError::generic_err(
+            "Runoff is only available for ranked choice elections",
+        ));
+    }
+    if RESULT.may_load(deps.storage)?.is_some() {
+        return Ok(RunoffResponse {
+            rounds: COUNTING.load(deps.storage)?.runoff.rounds,
+        });
+    }
+    Ok(RunoffResponse {
+        rounds: runoff(deps, &state)?,
+    })
+}
+
+fn query_eligible(
+    deps: Deps,
+    voter: String,
+    proof: Option<Vec<String>>,
+) -> StdResult<EligibilityResponse> {
+    let state = STATE.load(deps.storage)?;
+    let voter = deps.api.addr_validate(&voter)?;
+    let eligible = is_eligible(deps, &state, &voter, proof.as_deref())
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    Ok(EligibilityResponse { eligible })
+}
+
+fn query_turnout(deps: Deps) -> StdResult<TurnoutResponse> {
+    let state = STATE.load(deps.storage)?;
+    // committed ballots count towards turnout whether they are revealed or not
+    let ballots = BALLOT_COUNT.load(deps.storage)? + UNREVEALED.load(deps.storage)?;
+    let registered = state.registry.map(|registry| registry.voters());
+    let turnout = registered
+        .filter(|registered| *registered > 0)
+        .map(|registered| Decimal::from_ratio(ballots, registered));
+    Ok(TurnoutResponse {
+        ballots,
+        registered,
+        turnout,
+    })
+}
+
+fn assert_ended(state: &State, env: &Env) -> StdResult<()> {
+    if !state.closes_at().is_passed(&env.block) {
+        return Err(StdError::generic_err(format!(
+            "Voting has not ended, it ends at {}",
+            state.closes_at()
+        )));
+    }
+    Ok(())
+}
+
+fn runoff(deps: Deps, state: &State) -> StdResult<Vec<RunoffRound>> {
+    // abstentions take no part in the runoff
+    let ballots = BALLOTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, ballot)| ballot))
+        .filter(|ballot| !matches!(ballot, Ok(ballot) if ballot.candidates.is_empty()))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(instant_runoff(&state.candidates, &ballots))
+}
+
+// sorted_votes returns the count of every candidate, highest first.
+// Candidates with equal counts keep the order they were registered in.
+fn sorted_votes(deps: Deps, state: &State) -> StdResult<Vec<Vote>> {
+    let mut votes = state
+        .candidates
+        .iter()
+        .map(|candidate| {
+            Ok(Vote {
+                candidate: candidate.clone(),
+                count: TALLY.may_load(deps.storage, candidate)?.unwrap_or_default(),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    votes.sort_by_key(|vote| Reverse(vote.count));
+    Ok(votes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::{CandidateActions, NominationConfig, Status};
+    use crate::state::{LegacyState, StateV2};
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        attr, coin, coins, from_binary, from_slice, BankMsg, ContractResult, CosmosMsg, Empty,
+        OwnedDeps, Querier, QuerierResult, QueryRequest, SystemResult, WasmQuery,
+    };
+    use sha2::{Digest, Sha256};
+    use std::collections::HashMap;
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(20_000),
+            end: Scheduled::AtHeight(30_000),
+            candidates: vec!["alice".into()],
+            quorum: None,
+            threshold: None,
+            weight: None,
+            mode: None,
+            seats: None,
+            registry: None,
+            reveal_end: None,
+            owner: None,
+            actions: None,
+            nomination: None,
+        };
+        let info = mock_info("creator", &coins(1000, "earth"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Scheduled::AtHeight(20_000), value.start);
+        assert_eq!(Scheduled::AtHeight(30_000), value.end);
+    }
+
+    #[test]
+    fn vote() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let candidates: Vec<String> = vec!["candidates1".into(), "candidates2".into()];
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(10_000),
+            end: Scheduled::AtHeight(20_000),
+            candidates,
+            quorum: None,
+            threshold: None,
+            weight: None,
+            mode: None,
+            seats: None,
+            registry: None,
+            reveal_end: None,
+            owner: None,
+            actions: None,
+            nomination: None,
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // beneficiary can release it
+        let info = mock_info("voter1", &coins(2, "token"));
+        let msg = ExecuteMsg::Vote {
+            candidates: vec!["candidates1".into()],
+            proof: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // should increase counter by 1
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Scheduled::AtHeight(10_000), value.start);
+        assert_eq!(Scheduled::AtHeight(20_000), value.end);
+        assert_eq!("candidates1", value.votes[0].candidate);
+        assert_eq!(Uint128::new(1), value.votes[0].count);
+    }
+
+    #[test]
+    fn vote_twice() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(10_000),
+            end: Scheduled::AtHeight(20_000),
+            candidates: vec!["candidates1".into(), "candidates2".into()],
+            quorum: None,
+            threshold: None,
+            weight: None,
+            mode: None,
+            seats: None,
+            registry: None,
+            reveal_end: None,
+            owner: None,
+            actions: None,
+            nomination: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("voter1", &[]);
+        let msg = ExecuteMsg::Vote {
+            candidates: vec!["candidates1".into()],
+            proof: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // a second ballot from the same voter replaces the first one
+        let msg = ExecuteMsg::Vote {
+            candidates: vec!["candidates2".into()],
+            proof: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            votes(&[("candidates2", 1), ("candidates1", 0)]),
+            value.votes
+        );
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTurnout {}).unwrap();
+        let value: TurnoutResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.ballots);
+
+        // but only until voting ends
+        let msg = ExecuteMsg::Vote {
+            candidates: vec!["candidates1".into()],
+            proof: None,
+        };
+        let err = execute(deps.as_mut(), ended_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotAllowance { .. }));
+    }
+
+    #[test]
+    fn tally_many_voters() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(10_000),
+            end: Scheduled::AtHeight(20_000),
+            candidates: vec!["candidates1".into(), "candidates2".into()],
+            quorum: None,
+            threshold: None,
+            weight: None,
+            mode: None,
+            seats: None,
+            registry: None,
+            reveal_end: None,
+            owner: None,
+            actions: None,
+            nomination: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        for i in 0..300 {
+            let candidate = if i % 3 == 0 {
+                "candidates1"
+            } else {
+                "candidates2"
+            };
+            let info = mock_info(&format!("voter{}", i), &[]);
+            let msg = ExecuteMsg::Vote {
+                candidates: vec![candidate.into()],
+                proof: None,
+            };
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            vec![
+                Vote {
+                    candidate: "candidates2".into(),
+                    count: Uint128::new(200)
+                },
+                Vote {
+                    candidate: "candidates1".into(),
+                    count: Uint128::new(100)
+                }
+            ],
+            value.votes
+        );
+    }
+
+    fn instantiate_msg(candidates: &[&str]) -> InstantiateMsg {
+        InstantiateMsg {
+            start: Scheduled::AtHeight(10_000),
+            end: Scheduled::AtHeight(20_000),
+            candidates: candidates.iter().map(|c| c.to_string()).collect(),
+            quorum: None,
+            threshold: None,
+            weight: None,
+            mode: None,
+            seats: None,
+            registry: None,
+            reveal_end: None,
+            owner: None,
+            actions: None,
+            nomination: None,
+        }
+    }
+
+    fn setup_election(deps: DepsMut, msg: InstantiateMsg) {
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, msg).unwrap();
+    }
+
+    // submit_snapshot submits voting powers before the start of voting
+    fn submit_snapshot(deps: DepsMut, powers: &[(&str, u128)]) {
+        let mut env = mock_env();
+        env.block.height = 9_000;
+        let msg = ExecuteMsg::SubmitSnapshot {
+            powers: powers
+                .iter()
+                .map(|&(address, power)| SnapshotPower {
+                    address: address.into(),
+                    power: Uint128::new(power),
+                })
+                .collect(),
+        };
+        execute(deps, env, mock_info("creator", &[]), msg).unwrap();
+    }
+
+    fn cast(deps: DepsMut, voter: &str, candidate: &str) -> Result<Response, ContractError> {
+        let info = mock_info(voter, &[]);
+        let msg = ExecuteMsg::Vote {
+            candidates: vec![candidate.into()],
+            proof: None,
+        };
+        execute(deps, mock_env(), info, msg)
+    }
+
+    fn count(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+        let info = mock_info("anyone", &[]);
+        execute(deps, ended_env(), info, ExecuteMsg::Count { limit })
+    }
+
+    fn rank(deps: DepsMut, voter: &str, candidates: &[&str]) -> Result<Response, ContractError> {
+        let info = mock_info(voter, &[]);
+        let msg = ExecuteMsg::Vote {
+            candidates: candidates.iter().map(|c| c.to_string()).collect(),
+            proof: None,
+        };
+        execute(deps, mock_env(), info, msg)
+    }
+
+    fn votes(counts: &[(&str, u128)]) -> Vec<Vote> {
+        counts
+            .iter()
+            .map(|(candidate, count)| Vote {
+                candidate: candidate.to_string(),
+                count: Uint128::new(*count),
+            })
+            .collect()
+    }
+
+    fn ended_env() -> Env {
+        let mut env = mock_env();
+        env.block.height = 20_001;
+        env
+    }
+
+    #[test]
+    fn unknown_candidate() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(deps.as_mut(), instantiate_msg(&["alice", "bob"]));
+
+        let err = cast(deps.as_mut(), "voter1", "carol").unwrap_err();
+        match err {
+            ContractError::UnknownCandidate { candidate } => assert_eq!("carol", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn sorted_results() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            instantiate_msg(&["alice", "bob", "carol", "dave"]),
+        );
+
+        cast(deps.as_mut(), "voter1", "carol").unwrap();
+        cast(deps.as_mut(), "voter2", "carol").unwrap();
+        cast(deps.as_mut(), "voter3", "dave").unwrap();
+        cast(deps.as_mut(), "voter4", "alice").unwrap();
+
+        // every candidate is listed, ties keep the registration order
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        let order: Vec<(&str, u128)> = value
+            .votes
+            .iter()
+            .map(|vote| (vote.candidate.as_str(), vote.count.u128()))
+            .collect();
+        assert_eq!(
+            vec![("carol", 2), ("alice", 1), ("dave", 1), ("bob", 0)],
+            order
+        );
+    }
+
+    #[test]
+    fn winner() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                quorum: Some(Quorum::Ballots { count: 2 }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+
+        cast(deps.as_mut(), "voter1", "bob").unwrap();
+        cast(deps.as_mut(), "voter2", "bob").unwrap();
+        cast(deps.as_mut(), "voter3", "alice").unwrap();
+
+        // no winner while voting is still open
+        query(deps.as_ref(), mock_env(), QueryMsg::GetWinner {}).unwrap_err();
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.ballots);
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("bob", 2)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn winner_tie() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(deps.as_mut(), instantiate_msg(&["alice", "bob", "carol"]));
+
+        cast(deps.as_mut(), "voter1", "carol").unwrap();
+        cast(deps.as_mut(), "voter2", "alice").unwrap();
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Outcome::Tie {
+                elected: vec![],
+                tied: votes(&[("alice", 1), ("carol", 1)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn winner_quorum_not_met() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                quorum: Some(Quorum::Ballots { count: 3 }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "alice").unwrap();
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.ballots);
+        assert_eq!(Some(false), value.quorum_met);
+        assert_eq!(None, value.threshold_met);
+        assert_eq!(
+            Outcome::QuorumNotMet {
+                quorum: Quorum::Ballots { count: 3 }
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn abstain() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                quorum: Some(Quorum::Ballots { count: 4 }),
+                threshold: Some(Threshold::MoreThan {
+                    ratio: Decimal::percent(50),
+                }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "alice").unwrap();
+        cast(deps.as_mut(), "voter3", "bob").unwrap();
+        rank(deps.as_mut(), "voter4", &[]).unwrap();
+
+        // abstentions count towards the quorum but not towards the threshold
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(4, value.ballots);
+        assert_eq!(Uint128::new(4), value.weight);
+        assert_eq!(Uint128::new(1), value.abstained);
+        assert_eq!(Some(true), value.quorum_met);
+        assert_eq!(Some(true), value.threshold_met);
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("alice", 2)])
+            },
+            value.outcome
+        );
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(votes(&[("alice", 2), ("bob", 1)]), value.votes);
+
+        // a voter who changes their mind no longer abstains
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                threshold: Some(Threshold::AtLeast {
+                    ratio: Decimal::percent(60),
+                }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        rank(deps.as_mut(), "voter2", &[]).unwrap();
+        cast(deps.as_mut(), "voter3", "bob").unwrap();
+        cast(deps.as_mut(), "voter4", "alice").unwrap();
+        rank(deps.as_mut(), "voter4", &[]).unwrap();
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(2), value.abstained);
+        assert_eq!(None, value.quorum_met);
+        assert_eq!(Some(false), value.threshold_met);
+        assert_eq!(
+            Outcome::ThresholdNotMet {
+                threshold: Threshold::AtLeast {
+                    ratio: Decimal::percent(60)
+                }
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn quorum_rules() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                weight: Some(WeightSource::Snapshot {}),
+                quorum: Some(Quorum::Weight {
+                    amount: Uint128::new(100),
+                }),
+                // a supermajority of two thirds
+                threshold: Some(Threshold::AtLeast {
+                    ratio: Decimal::from_ratio(2u128, 3u128),
+                }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        submit_snapshot(deps.as_mut(), &[("voter1", 60), ("voter2", 30)]);
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "bob").unwrap();
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(false), value.quorum_met);
+        // exactly two thirds reach the threshold
+        assert_eq!(Some(true), value.threshold_met);
+        assert!(matches!(value.outcome, Outcome::QuorumNotMet { .. }));
+
+        // but do not exceed it
+        let mut state = STATE.load(deps.as_ref().storage).unwrap();
+        state.threshold = Some(Threshold::MoreThan {
+            ratio: Decimal::from_ratio(2u128, 3u128),
+        });
+        STATE.save(deps.as_mut().storage, &state).unwrap();
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(false), value.threshold_met);
+
+        let info = mock_info("creator", &[]);
+        let msg = InstantiateMsg {
+            quorum: Some(Quorum::Turnout {
+                ratio: Decimal::percent(50),
+            }),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::TurnoutNeedsRegistry {}));
+        let msg = InstantiateMsg {
+            threshold: Some(Threshold::AtLeast {
+                ratio: Decimal::percent(101),
+            }),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidThreshold {}));
+        // no share exceeds every ballot
+        let msg = InstantiateMsg {
+            threshold: Some(Threshold::MoreThan {
+                ratio: Decimal::one(),
+            }),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidThreshold {}));
+
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                quorum: Some(Quorum::Turnout {
+                    ratio: Decimal::percent(50),
+                }),
+                registry: Some(Registry::Voters {
+                    voters: vec!["voter1".into(), "voter2".into(), "voter3".into()],
+                }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(false), value.quorum_met);
+        cast(deps.as_mut(), "voter2", "alice").unwrap();
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(true), value.quorum_met);
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("alice", 2)])
+            },
+            value.outcome
+        );
+    }
+
+    // Cw4Querier answers member queries of a single cw4 group whose weights changed
+    // after the start of voting
+    struct Cw4Querier {
+        base: MockQuerier,
+        group: String,
+        at_start: HashMap<String, u64>,
+        latest: HashMap<String, u64>,
+    }
+
+    impl Querier for Cw4Querier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match request {
+                QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg })
+                    if contract_addr == self.group =>
+                {
+                    let weight = match from_binary(&msg).unwrap() {
+                        Cw4QueryMsg::Member {
+                            addr,
+                            at_height: Some(10_000),
+                        } => self.at_start.get(&addr).cloned(),
+                        Cw4QueryMsg::Member {
+                            addr,
+                            at_height: None,
+                        } => self.latest.get(&addr).cloned(),
+                        _ => panic!("unexpected cw4 query"),
+                    };
+                    let res = to_binary(&MemberResponse { weight }).unwrap();
+                    SystemResult::Ok(ContractResult::Ok(res))
+                }
+                _ => self.base.raw_query(bin_request),
+            }
+        }
+    }
+
+    #[test]
+    fn cw4_weight() {
+        let mut at_start = HashMap::new();
+        at_start.insert("voter1".to_string(), 5);
+        at_start.insert("voter2".to_string(), 3);
+        let mut latest = at_start.clone();
+        latest.insert("voter2".to_string(), 30);
+        latest.insert("voter3".to_string(), 10);
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: Cw4Querier {
+                base: MockQuerier::new(&[]),
+                group: "group".into(),
+                at_start,
+                latest,
+            },
+        };
+        let now = mock_env().block.time;
+        let msg = InstantiateMsg {
+            start: Scheduled::AtTime(now.plus_seconds(60)),
+            end: Scheduled::AtTime(now.plus_seconds(3_600)),
+            weight: Some(WeightSource::Cw4 {
+                address: "group".into(),
+            }),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::SnapshotHeight {}));
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                weight: Some(WeightSource::Cw4 {
+                    address: "group".into(),
+                }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+
+        // weights are read at the start height, later changes are ignored
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "bob").unwrap();
+        let err = cast(deps.as_mut(), "voter3", "bob").unwrap_err();
+        assert!(matches!(err, ContractError::NoVotingPower { .. }));
+
+        let msg = QueryMsg::GetVotingPower {
+            voter: "voter2".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: VotingPowerResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(3), value.power);
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("alice", 5)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn snapshot_weight() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.update_balance("voter3", coins(1_000, "ucosm"));
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                weight: Some(WeightSource::Snapshot {}),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        let mut env = mock_env();
+        env.block.height = 9_000;
+        let power = |address: &str, power: u128| SnapshotPower {
+            address: address.into(),
+            power: Uint128::new(power),
+        };
+
+        let msg = ExecuteMsg::SubmitSnapshot {
+            powers: vec![power("voter1", 10), power("voter2", 20)],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("voter1", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        // later batches add to or correct the snapshot
+        let msg = ExecuteMsg::SubmitSnapshot {
+            powers: vec![power("voter2", 0), power("voter4", 40)],
+        };
+        execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+
+        // the snapshot is final once voting started
+        let msg = ExecuteMsg::SubmitSnapshot {
+            powers: vec![power("voter3", 30)],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::VotingStarted {}));
+
+        for &(voter, expected) in
+            [("voter1", 10), ("voter2", 0), ("voter3", 0), ("voter4", 40)].iter()
+        {
+            let msg = QueryMsg::GetVotingPower {
+                voter: voter.into(),
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let value: VotingPowerResponse = from_binary(&res).unwrap();
+            assert_eq!(Uint128::new(expected), value.power);
+        }
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "alice").unwrap_err();
+        cast(deps.as_mut(), "voter3", "alice").unwrap_err();
+        cast(deps.as_mut(), "voter4", "bob").unwrap();
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("bob", 40)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn invalid_ballots() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(deps.as_mut(), instantiate_msg(&["alice", "bob"]));
+
+        // plurality ballots name a single candidate
+        let err = rank(deps.as_mut(), "voter1", &["alice", "bob"]).unwrap_err();
+        assert!(matches!(err, ContractError::TooManyCandidates { max: 1 }));
+
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                mode: Some(VotingMode::RankedChoice),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        let err = rank(deps.as_mut(), "voter1", &["bob", "alice", "bob"]).unwrap_err();
+        match err {
+            ContractError::DuplicateCandidate { candidate } => assert_eq!("bob", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn ranked_choice() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                mode: Some(VotingMode::RankedChoice),
+                ..instantiate_msg(&["alice", "bob", "carol", "dave"])
+            },
+        );
+
+        for i in 0..4 {
+            rank(deps.as_mut(), &format!("a{}", i), &["alice", "bob"]).unwrap();
+        }
+        for i in 0..3 {
+            rank(deps.as_mut(), &format!("b{}", i), &["bob", "carol"]).unwrap();
+        }
+        rank(deps.as_mut(), "c0", &["carol", "bob"]).unwrap();
+        rank(deps.as_mut(), "c1", &["carol", "bob"]).unwrap();
+        rank(deps.as_mut(), "c2", &["carol"]).unwrap();
+
+        // first preferences are tallied while voting is open
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!("alice", value.votes[0].candidate);
+        assert_eq!(Uint128::new(4), value.votes[0].count);
+        query(deps.as_ref(), mock_env(), QueryMsg::GetRunoff {}).unwrap_err();
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetRunoff {}).unwrap();
+        let value: RunoffResponse = from_binary(&res).unwrap();
+        let round = |counts: &[(&str, u128)], exhausted: u128, eliminated: &[&str]| RunoffRound {
+            votes: votes(counts),
+            exhausted: Uint128::new(exhausted),
+            eliminated: eliminated.iter().map(|c| c.to_string()).collect(),
+        };
+        assert_eq!(
+            vec![
+                round(
+                    &[("alice", 4), ("bob", 3), ("carol", 3), ("dave", 0)],
+                    0,
+                    &["dave"]
+                ),
+                round(
+                    &[("alice", 4), ("bob", 3), ("carol", 3)],
+                    0,
+                    &["bob", "carol"]
+                ),
+                round(&[("alice", 4)], 6, &[]),
+            ],
+            value.rounds
+        );
+        let rounds = value.rounds;
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("alice", 4)])
+            },
+            value.outcome
+        );
+        assert!(!value.counted);
+
+        // counting a few ballots at a time gives the same runoff
+        let mut messages = 0;
+        loop {
+            messages += 1;
+            let res = count(deps.as_mut(), Some(4)).unwrap();
+            let ballots = res.attributes[1].value.parse::<usize>().unwrap();
+            assert!(ballots <= 4);
+            if res.attributes[2].value == "true" {
+                assert_eq!(vec![election_event("finalized")], res.events);
+                break;
+            }
+            assert!(!res.events.contains(&election_event("finalized")));
+        }
+        assert!(messages > 1);
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetRunoff {}).unwrap();
+        let value: RunoffResponse = from_binary(&res).unwrap();
+        assert_eq!(rounds, value.rounds);
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert!(value.counted);
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("alice", 4)])
+            },
+            value.outcome
+        );
+        let err = count(deps.as_mut(), None).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyCounted {}));
+    }
+
+    #[test]
+    fn ranked_choice_transfers() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                mode: Some(VotingMode::RankedChoice),
+                ..instantiate_msg(&["alice", "bob", "carol"])
+            },
+        );
+
+        for i in 0..4 {
+            rank(deps.as_mut(), &format!("a{}", i), &["alice"]).unwrap();
+        }
+        for i in 0..3 {
+            rank(deps.as_mut(), &format!("b{}", i), &["bob", "carol"]).unwrap();
+        }
+        rank(deps.as_mut(), "c0", &["carol", "bob"]).unwrap();
+        rank(deps.as_mut(), "c1", &["carol", "bob"]).unwrap();
+
+        // carol is eliminated and her ballots carry bob past alice
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetRunoff {}).unwrap();
+        let value: RunoffResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.rounds.len());
+        assert_eq!(vec!["carol".to_string()], value.rounds[0].eliminated);
+        assert_eq!("bob", value.rounds[1].votes[0].candidate);
+        assert_eq!(Uint128::new(5), value.rounds[1].votes[0].count);
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("bob", 5)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn invalid_seats() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("creator", &[]);
+
+        let msg = InstantiateMsg {
+            seats: Some(3),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InvalidSeats {
+                seats: 3,
+                candidates: 2
+            }
+        ));
+
+        let msg = InstantiateMsg {
+            seats: Some(0),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSeats { seats: 0, .. }));
+
+        let msg = InstantiateMsg {
+            seats: Some(2),
+            mode: Some(VotingMode::RankedChoice),
+            ..instantiate_msg(&["alice", "bob", "carol"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::RankedChoiceSeats {}));
+    }
+
+    #[test]
+    fn invalid_window() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("creator", &[]);
+        let now = mock_env().block.time;
+
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(20_000),
+            end: Scheduled::AtHeight(10_000),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidWindow {}));
+
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(10_000),
+            end: Scheduled::AtHeight(10_000),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidWindow {}));
+
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(10_000),
+            end: Scheduled::AtTime(now.plus_seconds(3_600)),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::MixedWindow {}));
+
+        let msg = InstantiateMsg {
+            reveal_end: Some(Scheduled::AtTime(now.plus_seconds(3_600))),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::MixedWindow {}));
+
+        // the window must still be open when the election is created
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(10_000),
+            end: Scheduled::AtHeight(12_345),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::WindowInPast {}));
+
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            instantiate_msg(&[]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NoCandidates {}));
+
+        let msg = instantiate_msg(&["alice", "bob", "alice"]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(
+            matches!(err, ContractError::CandidateExists { candidate } if candidate == "alice")
+        );
+
+        // updates are held to the same rules
+        let msg = InstantiateMsg {
+            start: Scheduled::AtHeight(13_000),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::UpdateWindow {
+            start: Scheduled::AtHeight(15_000),
+            end: Scheduled::AtHeight(14_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidWindow {}));
+        let msg = ExecuteMsg::UpdateWindow {
+            start: Scheduled::AtHeight(11_000),
+            end: Scheduled::AtHeight(12_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::WindowInPast {}));
+        let msg = ExecuteMsg::UpdateWindow {
+            start: Scheduled::AtTime(now.plus_seconds(60)),
+            end: Scheduled::AtHeight(14_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::MixedWindow {}));
+    }
+
+    #[test]
+    fn time_window() {
+        let mut deps = mock_dependencies(&[]);
+        let now = mock_env().block.time;
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                start: Scheduled::AtTime(now.plus_seconds(60)),
+                end: Scheduled::AtTime(now.plus_seconds(3_600)),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+
+        let err = cast(deps.as_mut(), "voter1", "alice").unwrap_err();
+        assert!(matches!(err, ContractError::NotAllowance { .. }));
+
+        let mut env = mock_env();
+        env.block.time = now.plus_seconds(60);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Status::Open, value.status);
+        let msg = ExecuteMsg::Vote {
+            candidates: vec!["alice".into()],
+            proof: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("voter1", &[]), msg).unwrap();
+
+        // block height plays no part in a time based window
+        env.block.height = 1_000_000;
+        env.block.time = now.plus_seconds(3_600);
+        let msg = ExecuteMsg::Vote {
+            candidates: vec!["bob".into()],
+            proof: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("voter2", &[]), msg).unwrap();
+        query(deps.as_ref(), env.clone(), QueryMsg::GetWinner {}).unwrap_err();
+
+        env.block.time = now.plus_seconds(3_601);
+        let msg = ExecuteMsg::Vote {
+            candidates: vec!["bob".into()],
+            proof: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("voter3", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotAllowance { .. }));
+        let res = query(deps.as_ref(), env, QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.ballots);
+        assert!(matches!(value.outcome, Outcome::Tie { .. }));
+    }
+
+    #[test]
+    fn approval() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                mode: Some(VotingMode::Approval),
+                seats: Some(2),
+                ..instantiate_msg(&["alice", "bob", "carol", "dave"])
+            },
+        );
+
+        rank(deps.as_mut(), "voter1", &["alice", "bob", "carol"]).unwrap();
+        rank(deps.as_mut(), "voter2", &["bob", "dave"]).unwrap();
+        rank(deps.as_mut(), "voter3", &["carol", "bob"]).unwrap();
+        rank(deps.as_mut(), "voter4", &["carol"]).unwrap();
+        let err = rank(deps.as_mut(), "voter5", &["dave", "dave"]).unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateCandidate { .. }));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            votes(&[("bob", 3), ("carol", 3), ("alice", 1), ("dave", 1)]),
+            value.votes
+        );
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(4, value.ballots);
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("bob", 3), ("carol", 3)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn multiple_seats() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                seats: Some(2),
+                ..instantiate_msg(&["alice", "bob", "carol", "dave"])
+            },
+        );
+
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "alice").unwrap();
+        cast(deps.as_mut(), "voter3", "bob").unwrap();
+        cast(deps.as_mut(), "voter4", "carol").unwrap();
+
+        // bob and carol are tied for the second seat
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Outcome::Tie {
+                elected: votes(&[("alice", 2)]),
+                tied: votes(&[("bob", 1), ("carol", 1)])
+            },
+            value.outcome
+        );
+
+        cast(deps.as_mut(), "voter5", "carol").unwrap();
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("alice", 2), ("carol", 2)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn listed_voters() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                registry: Some(Registry::Voters {
+                    voters: vec!["voter1".into(), "voter2".into(), "voter1".into()],
+                }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTurnout {}).unwrap();
+        let value: TurnoutResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(2), value.registered);
+
+        // only the owner can change the voters, and only before voting starts
+        let mut env = mock_env();
+        env.block.height = 9_000;
+        let msg = ExecuteMsg::UpdateVoters {
+            add: vec!["voter3".into(), "voter4".into()],
+            remove: vec!["voter2".into()],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("voter1", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::VotingStarted {}));
+        execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+
+        let msg = QueryMsg::IsEligible {
+            voter: "voter2".into(),
+            proof: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: EligibilityResponse = from_binary(&res).unwrap();
+        assert!(!value.eligible);
+
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter3", "bob").unwrap();
+        let err = cast(deps.as_mut(), "voter2", "bob").unwrap_err();
+        match err {
+            ContractError::NotEligible { voter } => assert_eq!("voter2", voter),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTurnout {}).unwrap();
+        let value: TurnoutResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            TurnoutResponse {
+                ballots: 2,
+                registered: Some(3),
+                turnout: Some(Decimal::from_ratio(2u64, 3u64)),
+            },
+            value
+        );
+    }
+
+    fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let mut hashes = [*a, *b];
+        hashes.sort_unstable();
+        Sha256::digest(&hashes.concat()).into()
+    }
+
+    #[test]
+    fn merkle_voters() {
+        // merkle tree over four voters
+        let leaves: Vec<[u8; 32]> = ["voter1", "voter2", "voter3", "voter4"]
+            .iter()
+            .map(|voter| Sha256::digest(voter.as_bytes()).into())
+            .collect();
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        let right = hash_pair(&leaves[2], &leaves[3]);
+        let root = hex::encode(hash_pair(&left, &right));
+        let proof = |sibling: &[u8; 32], node: &[u8; 32]| {
+            Some(vec![hex::encode(sibling), hex::encode(node)])
+        };
+
+        // the root must be a hex encoded sha256 hash
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("creator", &[]);
+        let msg = InstantiateMsg {
+            registry: Some(Registry::Merkle {
+                root: "00".into(),
+                voters: 4,
+            }),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidProof {}));
+
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                registry: Some(Registry::Merkle { root, voters: 4 }),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+
+        let vote = |candidate: &str, proof: Option<Vec<String>>| ExecuteMsg::Vote {
+            candidates: vec![candidate.into()],
+            proof,
+        };
+        let msg = vote("alice", proof(&leaves[1], &right));
+        execute(deps.as_mut(), mock_env(), mock_info("voter1", &[]), msg).unwrap();
+        let msg = vote("bob", proof(&leaves[2], &left));
+        execute(deps.as_mut(), mock_env(), mock_info("voter4", &[]), msg).unwrap();
+
+        // a proof only opens the leaf it was built for
+        let msg = vote("bob", proof(&leaves[1], &right));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("voter2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotEligible { .. }));
+        let msg = vote("bob", None);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("voter2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotEligible { .. }));
+
+        let msg = QueryMsg::IsEligible {
+            voter: "voter2".into(),
+            proof: proof(&leaves[0], &right),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: EligibilityResponse = from_binary(&res).unwrap();
+        assert!(value.eligible);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTurnout {}).unwrap();
+        let value: TurnoutResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.ballots);
+        assert_eq!(Some(Decimal::percent(50)), value.turnout);
+    }
+
+    #[test]
+    fn secret_ballots() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                reveal_end: Some(Scheduled::AtHeight(30_000)),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+
+        // public ballots are refused
+        let err = cast(deps.as_mut(), "voter1", "alice").unwrap_err();
+        assert!(matches!(err, ContractError::SecretBallot {}));
+
+        let commit = |candidate: &str, salt: &str| ExecuteMsg::Commit {
+            commitment: ballot_commitment(&[candidate.to_string()], salt),
+            proof: None,
+        };
+        let reveal = |candidate: &str, salt: &str| ExecuteMsg::Reveal {
+            candidates: vec![candidate.into()],
+            salt: salt.into(),
+        };
+        for &(voter, candidate) in
+            [("voter1", "alice"), ("voter2", "bob"), ("voter3", "bob")].iter()
+        {
+            let msg = commit(candidate, voter);
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), msg).unwrap();
+        }
+        let msg = commit("alice", "voter1");
+        let err = execute(deps.as_mut(), mock_env(), mock_info("voter1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyVoted { .. }));
+
+        // nothing is counted while ballots are committed
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(votes(&[("alice", 0), ("bob", 0)]), value.votes);
+        assert_eq!(3, value.unrevealed);
+        let msg = QueryMsg::HasVoted {
+            voter: "voter1".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        assert!(from_binary::<HasVotedResponse>(&res).unwrap().voted);
+        let msg = QueryMsg::GetBallot {
+            voter: "voter1".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        assert_eq!(None, from_binary::<BallotResponse>(&res).unwrap().ballot);
+
+        // ballots can only be revealed after voting ended
+        let msg = reveal("alice", "voter1");
+        let err = execute(deps.as_mut(), mock_env(), mock_info("voter1", &[]), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::NotRevealPeriod {
+                end: Scheduled::AtHeight(20_000),
+                reveal_end: Scheduled::AtHeight(30_000),
+            }
+        ));
+
+        // revealed ballots are reported like public ones
+        let msg = reveal("alice", "voter1");
+        let res = execute(deps.as_mut(), ended_env(), mock_info("voter1", &[]), msg).unwrap();
+        assert_eq!(
+            vec![
+                attr("action", "vote"),
+                attr("method", "reveal"),
+                attr("voter", "voter1"),
+                attr("candidate", "alice"),
+                attr("weight", "1"),
+            ],
+            res.attributes
+        );
+        let msg = reveal("alice", "voter2");
+        let err = execute(deps.as_mut(), ended_env(), mock_info("voter2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::CommitmentMismatch {}));
+        let msg = reveal("bob", "voter2");
+        execute(deps.as_mut(), ended_env(), mock_info("voter2", &[]), msg).unwrap();
+        let msg = reveal("bob", "voter4");
+        let err = execute(deps.as_mut(), ended_env(), mock_info("voter4", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoCommitment { .. }));
+
+        // the winner is only known once the reveal period is over
+        query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap_err();
+        let mut env = mock_env();
+        env.block.height = 30_001;
+        let msg = reveal("bob", "voter3");
+        let err = execute(deps.as_mut(), env.clone(), mock_info("voter3", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotRevealPeriod { .. }));
+
+        let res = query(deps.as_ref(), env, QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.ballots);
+        assert_eq!(1, value.unrevealed);
+        assert_eq!(
+            Outcome::Tie {
+                elected: vec![],
+                tied: votes(&[("alice", 1), ("bob", 1)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn owner_administration() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(deps.as_mut(), instantiate_msg(&["alice", "bob"]));
+        let mut env = mock_env();
+        env.block.height = 9_000;
+        let owner = mock_info("creator", &[]);
+
+        let msg = ExecuteMsg::UpdateCandidates {
+            add: vec!["carol".into()],
+            remove: vec!["alice".into()],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateCandidates {
+            add: vec!["bob".into()],
+            remove: vec![],
+        };
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::CandidateExists { .. }));
+
+        let msg = ExecuteMsg::UpdateWindow {
+            start: Scheduled::AtHeight(11_000),
+            end: Scheduled::AtHeight(13_000),
+        };
+        execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+
+        let res = query(deps.as_ref(), env, QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Status::Upcoming, value.status);
+        assert_eq!(Scheduled::AtHeight(11_000), value.start);
+        assert_eq!(Scheduled::AtHeight(13_000), value.end);
+        assert_eq!(votes(&[("bob", 0), ("carol", 0)]), value.votes);
+
+        // nothing can be changed once voting started
+        let msg = ExecuteMsg::UpdateWindow {
+            start: Scheduled::AtHeight(13_000),
+            end: Scheduled::AtHeight(14_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), owner, msg).unwrap_err();
+        assert!(matches!(err, ContractError::VotingStarted {}));
+        let err = cast(deps.as_mut(), "voter1", "alice").unwrap_err();
+        assert!(matches!(err, ContractError::UnknownCandidate { .. }));
+        cast(deps.as_mut(), "voter1", "carol").unwrap();
+    }
+
+    #[test]
+    fn cancel() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                owner: Some("admin".into()),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+
+        // an ended election can not be cancelled
+        let err = execute(
+            deps.as_mut(),
+            ended_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::Cancel {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::VotingEnded {}));
+
+        // the owner given at instantiation replaces the sender
+        for sender in ["voter1", "creator"].iter() {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                ExecuteMsg::Cancel {},
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::Unauthorized {}));
+        }
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            ExecuteMsg::Cancel {},
+        )
+        .unwrap();
+        assert_eq!(vec![election_event("cancelled")], res.events);
+
+        let err = cast(deps.as_mut(), "voter2", "bob").unwrap_err();
+        assert!(matches!(err, ContractError::Cancelled {}));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Status::Cancelled, value.status);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(Outcome::Cancelled {}, value.outcome);
+    }
+
+    #[test]
+    fn delegation() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                weight: Some(WeightSource::Snapshot {}),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        submit_snapshot(
+            deps.as_mut(),
+            &[("voter1", 100), ("voter2", 200), ("voter3", 400)],
+        );
+        let delegate = |deps: DepsMut, delegator: &str, delegate: &str| {
+            let msg = ExecuteMsg::Delegate {
+                delegate: delegate.into(),
+                proof: None,
+            };
+            execute(deps, mock_env(), mock_info(delegator, &[]), msg)
+        };
+        let tally = |deps: Deps| {
+            let res = query(deps, mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+            from_binary::<VoteResponse>(&res).unwrap().votes
+        };
+
+        // delegates vote with the power of their delegators, whenever they delegated
+        delegate(deps.as_mut(), "voter1", "delegate").unwrap();
+        cast(deps.as_mut(), "delegate", "alice").unwrap();
+        delegate(deps.as_mut(), "voter2", "delegate").unwrap();
+        assert_eq!(votes(&[("alice", 300), ("bob", 0)]), tally(deps.as_ref()));
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDelegation {
+                voter: "delegate".into(),
+            },
+        )
+        .unwrap();
+        let value: DelegationResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.delegate);
+        assert_eq!(Uint128::new(300), value.delegated);
+
+        // changing the vote of the delegate moves all of its power
+        cast(deps.as_mut(), "delegate", "bob").unwrap();
+        assert_eq!(votes(&[("bob", 300), ("alice", 0)]), tally(deps.as_ref()));
+
+        // delegators override their delegate by voting directly
+        cast(deps.as_mut(), "voter2", "alice").unwrap();
+        assert_eq!(votes(&[("alice", 200), ("bob", 100)]), tally(deps.as_ref()));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("voter2", &[]),
+            ExecuteMsg::Undelegate {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotDelegated { .. }));
+
+        // delegating withdraws the ballot of the delegator
+        delegate(deps.as_mut(), "voter3", "voter2").unwrap();
+        assert_eq!(votes(&[("alice", 600), ("bob", 100)]), tally(deps.as_ref()));
+        delegate(deps.as_mut(), "voter2", "delegate").unwrap_err();
+        delegate(deps.as_mut(), "voter3", "delegate").unwrap();
+        assert_eq!(votes(&[("bob", 500), ("alice", 200)]), tally(deps.as_ref()));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("voter1", &[]),
+            ExecuteMsg::Undelegate {},
+        )
+        .unwrap();
+        assert_eq!(votes(&[("bob", 400), ("alice", 200)]), tally(deps.as_ref()));
+
+        // delegations are one level deep
+        let err = delegate(deps.as_mut(), "delegate", "voter1").unwrap_err();
+        assert!(matches!(err, ContractError::DelegationChain {}));
+        let err = delegate(deps.as_mut(), "voter1", "voter3").unwrap_err();
+        assert!(matches!(err, ContractError::DelegationChain {}));
+        let err = delegate(deps.as_mut(), "voter1", "voter1").unwrap_err();
+        assert!(matches!(err, ContractError::SelfDelegation {}));
+
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.ballots);
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("bob", 400)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn execute_actions() {
+        let mut deps = mock_dependencies(&[]);
+        let payout = |to: &str| -> CosmosMsg {
+            BankMsg::Send {
+                to_address: to.into(),
+                amount: coins(100, "ucosm"),
+            }
+            .into()
+        };
+        let msg = InstantiateMsg {
+            actions: Some(vec![CandidateActions {
+                candidate: "carol".into(),
+                msgs: vec![],
+            }]),
+            ..instantiate_msg(&["alice", "bob"])
+        };
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::UnknownCandidate { .. }));
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                actions: Some(vec![
+                    CandidateActions {
+                        candidate: "alice".into(),
+                        msgs: vec![payout("alice"), payout("treasury")],
+                    },
+                    CandidateActions {
+                        candidate: "bob".into(),
+                        msgs: vec![payout("bob")],
+                    },
+                ]),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "alice").unwrap();
+        cast(deps.as_mut(), "voter3", "bob").unwrap();
+
+        let info = mock_info("anyone", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Count { limit: None },
+        )
+        .unwrap_err();
+        let err = execute(
+            deps.as_mut(),
+            ended_env(),
+            info.clone(),
+            ExecuteMsg::Execute {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotCounted {}));
+        count(deps.as_mut(), None).unwrap();
+        let res = execute(
+            deps.as_mut(),
+            ended_env(),
+            info.clone(),
+            ExecuteMsg::Execute {},
+        )
+        .unwrap();
+        let msgs: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(vec![payout("alice"), payout("treasury")], msgs);
+
+        // the actions are only dispatched once
+        let err = execute(deps.as_mut(), ended_env(), info, ExecuteMsg::Execute {}).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyExecuted {}));
+        let msg = QueryMsg::GetActions {
+            candidate: "bob".into(),
+        };
+        let res = query(deps.as_ref(), ended_env(), msg).unwrap();
+        let value: ActionsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![payout("bob")], value.msgs);
+        assert!(value.executed);
+    }
+
+    #[test]
+    fn execute_without_winner() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(deps.as_mut(), instantiate_msg(&["alice", "bob"]));
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "bob").unwrap();
+        count(deps.as_mut(), None).unwrap();
+
+        let info = mock_info("anyone", &[]);
+        let err = execute(deps.as_mut(), ended_env(), info, ExecuteMsg::Execute {}).unwrap_err();
+        assert!(matches!(err, ContractError::NoWinner {}));
+    }
+
+    #[test]
+    fn migrate_legacy_election() {
+        let mut deps = mock_dependencies(&[]);
+        let vote = |voter: &str, candidate: &str| VoteInfo {
+            voter: voter.into(),
+            candidate: candidate.into(),
+        };
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let legacy = LegacyState {
+            start: 10_000,
+            end: 20_000,
+            candidates: vec!["alice".into(), "bob".into()],
+            votes: vec![
+                vote("voter1", "alice"),
+                vote("voter2", "bob"),
+                vote("voter1", "bob"),
+                vote("voter3", "carol"),
+                vote("voter3", "alice"),
+                vote("voter5", "alice"),
+                vote("voter5", "carol"),
+            ],
+        };
+        LEGACY_STATE.save(deps.as_mut().storage, &legacy).unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+        assert!(matches!(err, ContractError::MissingOwner { .. }));
+        let msg = MigrateMsg {
+            owner: Some("admin".into()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(CONTRACT_VERSION, version.version);
+
+        // the last vote of every voter is kept as their ballot,
+        // voter5 last voted for someone who is not a candidate
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Status::Open, value.status);
+        assert_eq!(Scheduled::AtHeight(10_000), value.start);
+        assert_eq!(Scheduled::AtHeight(20_000), value.end);
+        assert_eq!(votes(&[("bob", 2), ("alice", 1)]), value.votes);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetTurnout {}).unwrap();
+        let value: TurnoutResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.ballots);
+
+        // the migrated election keeps working
+        cast(deps.as_mut(), "voter4", "alice").unwrap();
+        cast(deps.as_mut(), "voter2", "alice").unwrap();
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(Addr::unchecked("admin"), state.owner);
+        let res = query(deps.as_ref(), ended_env(), QueryMsg::GetWinner {}).unwrap();
+        let value: WinnerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            Outcome::Elected {
+                winners: votes(&[("alice", 3)])
+            },
+            value.outcome
+        );
+    }
+
+    #[test]
+    fn migrate_election_without_candidates() {
+        let mut deps = mock_dependencies(&[]);
+        let vote = |voter: &str, candidate: &str| VoteInfo {
+            voter: voter.into(),
+            candidate: candidate.into(),
+        };
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let legacy = LegacyState {
+            start: 10_000,
+            end: 20_000,
+            candidates: vec![],
+            votes: vec![
+                vote("voter1", "carol"),
+                vote("voter2", "dave"),
+                vote("voter1", "dave"),
+                vote("voter3", "erin"),
+            ],
+        };
+        LEGACY_STATE.save(deps.as_mut().storage, &legacy).unwrap();
+        let msg = MigrateMsg {
+            owner: Some("admin".into()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+        // the names voted for last become the candidates
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            vec!["dave".to_string(), "erin".to_string()],
+            state.candidates
+        );
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(votes(&[("dave", 2), ("erin", 1)]), value.votes);
+    }
+
+    #[test]
+    fn migrate_v2_election() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(deps.as_mut(), instantiate_msg(&["alice", "bob"]));
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        let v2 = StateV2 {
+            owner: state.owner,
+            start: state.start,
+            end: state.end,
+            candidates: state.candidates,
+            quorum: None,
+            threshold: Some(Decimal::percent(50)),
+            weight: None,
+            mode: state.mode,
+            seats: state.seats,
+            registry: None,
+            reveal_end: None,
+            cancelled: false,
+            executed: false,
+            nomination: None,
+        };
+        STATE_V2.save(deps.as_mut().storage, &v2).unwrap();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.2.0").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap();
+
+        // a 0.2 threshold was met by reaching it
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            Some(Threshold::AtLeast {
+                ratio: Decimal::percent(50)
+            }),
+            state.threshold
+        );
+        assert_eq!("creator", state.owner.as_str());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(votes(&[("alice", 1), ("bob", 0)]), value.votes);
+
+        // live balances fixed no voting power at start to carry over
+        let v2 = StateV2 {
+            weight: Some(WeightSourceV2::Native {
+                denom: "ucosm".into(),
+            }),
+            ..v2
+        };
+        STATE_V2.save(deps.as_mut().storage, &v2).unwrap();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.2.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+        assert!(matches!(err, ContractError::LiveWeight {}));
+    }
+
+    #[test]
+    fn migrate_refused() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(deps.as_mut(), instantiate_msg(&["alice", "bob"]));
+        cast(deps.as_mut(), "voter1", "alice").unwrap();
+
+        // migrating to the same version keeps the election as it is
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(votes(&[("alice", 1), ("bob", 0)]), value.votes);
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+        match err {
+            ContractError::CannotDowngrade { stored, current } => {
+                assert_eq!("99.0.0", stored);
+                assert_eq!(CONTRACT_VERSION, current);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        set_contract_version(deps.as_mut().storage, "crates.io:collector", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrate { .. }));
+    }
+
+    #[test]
+    fn ballot_queries() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                mode: Some(VotingMode::RankedChoice),
+                ..instantiate_msg(&["alice", "bob"])
+            },
+        );
+        for voter in ["voter3", "voter1", "voter4", "voter2"].iter() {
+            rank(deps.as_mut(), voter, &["bob", "alice"]).unwrap();
+        }
+        rank(deps.as_mut(), "voter2", &["alice"]).unwrap();
+
+        let msg = QueryMsg::GetBallot {
+            voter: "voter2".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: BallotResponse = from_binary(&res).unwrap();
+        let ballot = |voter: &str, candidates: &[&str]| BallotInfo {
+            voter: voter.into(),
+            candidates: candidates.iter().map(|c| c.to_string()).collect(),
+            weight: Uint128::new(1),
+        };
+        assert_eq!(Some(ballot("voter2", &["alice"])), value.ballot);
+        let msg = QueryMsg::GetBallot {
+            voter: "voter5".into(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: BallotResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.ballot);
+
+        let list = |start_after: Option<&str>| {
+            let msg = QueryMsg::ListBallots {
+                start_after: start_after.map(String::from),
+                limit: Some(3),
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            from_binary::<BallotsResponse>(&res).unwrap().ballots
+        };
+        assert_eq!(
+            vec![
+                ballot("voter1", &["bob", "alice"]),
+                ballot("voter2", &["alice"]),
+                ballot("voter3", &["bob", "alice"]),
+            ],
+            list(None)
+        );
+        assert_eq!(
+            vec![ballot("voter4", &["bob", "alice"])],
+            list(Some("voter3"))
+        );
+        assert!(list(Some("voter4")).is_empty());
+
+        for &(voter, voted) in [("voter1", true), ("voter5", false)].iter() {
+            let msg = QueryMsg::HasVoted {
+                voter: voter.into(),
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let value: HasVotedResponse = from_binary(&res).unwrap();
+            assert_eq!(voted, value.voted);
+        }
+    }
+
+    #[test]
+    fn vote_events() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                start: Scheduled::AtHeight(20_000),
+                end: Scheduled::AtHeight(30_000),
+                mode: Some(VotingMode::Approval),
+                ..instantiate_msg(&["alice", "bob", "carol"])
+            },
+        );
+        let at = |height| {
+            let mut env = mock_env();
+            env.block.height = height;
+            env
+        };
+        let vote = |deps: DepsMut, env, voter: &str, candidates: &[&str]| {
+            let msg = ExecuteMsg::Vote {
+                candidates: candidates.iter().map(|c| c.to_string()).collect(),
+                proof: None,
+            };
+            execute(deps, env, mock_info(voter, &[]), msg).unwrap()
+        };
+
+        // the first message after the start reports the opening
+        let res = vote(deps.as_mut(), at(20_000), "voter1", &["alice"]);
+        assert_eq!(vec![election_event("opened")], res.events);
+        assert_eq!(
+            vec![
+                attr("action", "vote"),
+                attr("voter", "voter1"),
+                attr("candidate", "alice"),
+                attr("weight", "1"),
+            ],
+            res.attributes
+        );
+        let res = vote(deps.as_mut(), at(20_001), "voter2", &["alice", "carol"]);
+        assert!(res.events.is_empty());
+        assert_eq!(attr("candidate", "alice,carol"), res.attributes[2]);
+        let res = vote(deps.as_mut(), at(20_002), "voter3", &[]);
+        assert_eq!(attr("candidate", ""), res.attributes[2]);
+
+        let res = execute(
+            deps.as_mut(),
+            at(30_001),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Count { limit: None },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![election_event("closed"), election_event("finalized")],
+            res.events
+        );
+        let res = execute(
+            deps.as_mut(),
+            at(30_002),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Execute {},
+        )
+        .unwrap();
+        assert!(res.events.is_empty());
+    }
+
+    #[test]
+    fn nomination() {
+        let mut deps = mock_dependencies(&[]);
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(&[]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NoCandidates {}));
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                start: Scheduled::AtHeight(20_000),
+                end: Scheduled::AtHeight(30_000),
+                nomination: Some(NominationConfig {
+                    deposit: coin(100, "ucosm"),
+                    min_share: Decimal::percent(20),
+                    treasury: "treasury".into(),
+                }),
+                ..instantiate_msg(&[])
+            },
+        );
+        let at = |height| {
+            let mut env = mock_env();
+            env.block.height = height;
+            env
+        };
+        let nominate = |deps: DepsMut, env, nominee: &str, funds: &[Coin]| {
+            execute(
+                deps,
+                env,
+                mock_info(nominee, funds),
+                ExecuteMsg::Nominate {},
+            )
+        };
+
+        for funds in [vec![], coins(50, "ucosm"), coins(100, "uatom")].iter() {
+            let err = nominate(deps.as_mut(), mock_env(), "alice", funds).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidDeposit { .. }));
+        }
+        for nominee in ["alice", "bob", "carol", "dave"].iter() {
+            nominate(deps.as_mut(), mock_env(), nominee, &coins(100, "ucosm")).unwrap();
+        }
+        let err = nominate(deps.as_mut(), mock_env(), "alice", &coins(100, "ucosm")).unwrap_err();
+        assert!(matches!(err, ContractError::CandidateExists { .. }));
+
+        // removed nominees are refunded at once
+        let msg = ExecuteMsg::UpdateCandidates {
+            add: vec![],
+            remove: vec!["carol".into()],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msgs: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            vec![CosmosMsg::from(BankMsg::Send {
+                to_address: "carol".into(),
+                amount: coins(100, "ucosm"),
+            })],
+            msgs
+        );
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetNomination {}).unwrap();
+        let value: NominationResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["alice", "bob", "dave"], value.nominees);
+        assert!(!value.settled);
+
+        let err = nominate(deps.as_mut(), at(20_000), "erin", &coins(100, "ucosm")).unwrap_err();
+        assert!(matches!(err, ContractError::VotingStarted {}));
+        for (voter, candidates) in vec![
+            ("voter1", vec!["alice".to_string()]),
+            ("voter2", vec!["alice".to_string()]),
+            ("voter3", vec!["alice".to_string()]),
+            ("voter4", vec!["alice".to_string()]),
+            ("voter5", vec!["bob".to_string()]),
+            ("voter6", vec![]),
+        ] {
+            let msg = ExecuteMsg::Vote {
+                candidates,
+                proof: None,
+            };
+            execute(deps.as_mut(), at(20_000), mock_info(voter, &[]), msg).unwrap();
+        }
+
+        let info = mock_info("anyone", &[]);
+        execute(
+            deps.as_mut(),
+            at(30_000),
+            info.clone(),
+            ExecuteMsg::Finalize {},
+        )
+        .unwrap_err();
+        // bob reached exactly the minimum share, dave forfeits the deposit
+        let res = execute(
+            deps.as_mut(),
+            at(30_001),
+            info.clone(),
+            ExecuteMsg::Finalize {},
+        )
+        .unwrap();
+        let msgs: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        let send = |to: &str| -> CosmosMsg {
+            BankMsg::Send {
+                to_address: to.into(),
+                amount: coins(100, "ucosm"),
+            }
+            .into()
+        };
+        assert_eq!(vec![send("alice"), send("bob"), send("treasury")], msgs);
+        let err = execute(deps.as_mut(), at(30_001), info, ExecuteMsg::Finalize {}).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadySettled {}));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetNomination {}).unwrap();
+        let value: NominationResponse = from_binary(&res).unwrap();
+        assert!(value.nominees.is_empty());
+        assert!(value.settled);
+    }
+
+    #[test]
+    fn nomination_cancelled() {
+        let mut deps = mock_dependencies(&[]);
+        setup_election(deps.as_mut(), instantiate_msg(&["alice"]));
+        let info = mock_info("bob", &coins(100, "ucosm"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Nominate {}).unwrap_err();
+        assert!(matches!(err, ContractError::NoNomination {}));
+
+        let mut deps = mock_dependencies(&[]);
+        setup_election(
+            deps.as_mut(),
+            InstantiateMsg {
+                start: Scheduled::AtHeight(20_000),
+                end: Scheduled::AtHeight(30_000),
+                nomination: Some(NominationConfig {
+                    deposit: coin(100, "ucosm"),
+                    min_share: Decimal::percent(20),
+                    treasury: "treasury".into(),
+                }),
+                ..instantiate_msg(&["alice"])
+            },
+        );
+        let info = mock_info("bob", &coins(100, "ucosm"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Nominate {}).unwrap();
+        let info = mock_info("creator", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Cancel {},
+        )
+        .unwrap();
+
+        // every deposit is refunded without waiting for the end
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Finalize {}).unwrap();
+        let msgs: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+        assert_eq!(
+            vec![CosmosMsg::from(BankMsg::Send {
+                to_address: "bob".into(),
+                amount: coins(100, "ucosm"),
+            })],
+            msgs
+        );
     }
 }
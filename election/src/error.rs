@@ -1,16 +1,156 @@
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
+use crate::msg::Scheduled;
+
 #[derive(Error, Debug)]
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Semver(#[from] semver::Error),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Cannot migrate from {contract}")]
+    CannotMigrate { contract: String },
+
+    #[error("Cannot migrate from version {stored} to older version {current}")]
+    CannotDowngrade { stored: String, current: String },
+
+    #[error("Migrating from version {version} needs an owner")]
+    MissingOwner { version: String },
+
     #[error("Voting time is out of range,shoule be ({begin}, {end})")]
-    NotAllowance { begin: u64, end: u64 },
+    NotAllowance { begin: Scheduled, end: Scheduled },
+
+    #[error("Voting window must use either heights or times")]
+    MixedWindow {},
+
+    #[error("Voting must end after it starts")]
+    InvalidWindow {},
+
+    #[error("Voting window has already passed")]
+    WindowInPast {},
+
+    #[error("Election needs at least one candidate")]
+    NoCandidates {},
+
+    #[error("{voter} has already voted")]
+    AlreadyVoted { voter: String },
+
+    #[error("Cannot delegate to yourself")]
+    SelfDelegation {},
+
+    #[error("Delegated votes cannot be delegated again")]
+    DelegationChain {},
+
+    #[error("{voter} has not delegated their vote")]
+    NotDelegated { voter: String },
+
+    #[error("{candidate} is not a candidate")]
+    UnknownCandidate { candidate: String },
+
+    #[error("Ballot names {candidate} more than once")]
+    DuplicateCandidate { candidate: String },
+
+    #[error("Ballot names too many candidates, at most {max} allowed")]
+    TooManyCandidates { max: usize },
+
+    #[error("Cannot fill {seats} seats from {candidates} candidates")]
+    InvalidSeats { seats: u32, candidates: usize },
+
+    #[error("Ranked choice elections fill a single seat")]
+    RankedChoiceSeats {},
+
+    #[error("{voter} is not eligible to vote")]
+    NotEligible { voter: String },
+
+    #[error("Invalid merkle proof or root")]
+    InvalidProof {},
+
+    #[error("Election does not use this kind of voter registry")]
+    RegistryMismatch {},
+
+    #[error("Voting has already started")]
+    VotingStarted {},
+
+    #[error("Voting has already ended")]
+    VotingEnded {},
+
+    #[error("A cw4 snapshot needs a voting window given in heights")]
+    SnapshotHeight {},
+
+    #[error("Voting power does not come from a submitted snapshot")]
+    WeightMismatch {},
+
+    #[error("Elections weighed by live balances can not be migrated")]
+    LiveWeight {},
+
+    #[error("A turnout quorum needs a voter registry")]
+    TurnoutNeedsRegistry {},
+
+    #[error("Turnout quorum must not be above 1")]
+    InvalidQuorum {},
+
+    #[error("Threshold must not be above 1, or reach 1 when it must be exceeded")]
+    InvalidThreshold {},
+
+    #[error("The election has no winner")]
+    NoWinner {},
+
+    #[error("The winning actions have already been executed")]
+    AlreadyExecuted {},
+
+    #[error("The ballots have not been counted, send Count first")]
+    NotCounted {},
+
+    #[error("The ballots have already been counted")]
+    AlreadyCounted {},
+
+    #[error("Election has been cancelled")]
+    Cancelled {},
+
+    #[error("{candidate} is already a candidate")]
+    CandidateExists { candidate: String },
+
+    #[error("Ballots are secret and must be committed")]
+    SecretBallot {},
+
+    #[error("Ballots are public and cannot be committed")]
+    PublicBallot {},
+
+    #[error("Reveal end must be after the end of voting")]
+    InvalidRevealEnd {},
+
+    #[error("Reveal time is out of range, should be after {end} until {reveal_end}")]
+    NotRevealPeriod {
+        end: Scheduled,
+        reveal_end: Scheduled,
+    },
+
+    #[error("{voter} has no committed ballot")]
+    NoCommitment { voter: String },
+
+    #[error("Ballot does not match the commitment")]
+    CommitmentMismatch {},
+
+    #[error("{voter} has no voting power")]
+    NoVotingPower { voter: String },
+
+    #[error("Election has no nomination phase")]
+    NoNomination {},
+
+    #[error("Nomination needs a deposit of {deposit}")]
+    InvalidDeposit { deposit: String },
+
+    #[error("Minimum share must not be above 1")]
+    InvalidMinShare {},
+
+    #[error("Deposits have already been settled")]
+    AlreadySettled {},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -1,4 +1,7 @@
+pub mod commitment;
 pub mod contract;
 pub mod error;
+pub mod merkle;
 pub mod msg;
+pub mod runoff;
 pub mod state;
@@ -0,0 +1,28 @@
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+
+// verify_proof checks that voter is a leaf of the merkle tree with the given root.
+// Leaves are the sha256 hash of the address and every pair of nodes is hashed in
+// sorted order, so a proof is just the list of hex encoded sibling hashes.
+pub fn verify_proof(root: &str, voter: &str, proof: &[String]) -> Result<bool, ContractError> {
+    let root = decode_hash(root)?;
+    let leaf: [u8; 32] = Sha256::digest(voter.as_bytes()).into();
+    let hash = proof.iter().try_fold(leaf, |hash, sibling| {
+        let mut hashes = [hash, decode_hash(sibling)?];
+        hashes.sort_unstable();
+        Ok::<_, ContractError>(Sha256::digest(&hashes.concat()).into())
+    })?;
+    Ok(hash == root)
+}
+
+// validate_root checks that root is a hex encoded sha256 hash
+pub fn validate_root(root: &str) -> Result<(), ContractError> {
+    decode_hash(root).map(|_| ())
+}
+
+fn decode_hash(hash: &str) -> Result<[u8; 32], ContractError> {
+    let mut buf = [0u8; 32];
+    hex::decode_to_slice(hash, &mut buf).map_err(|_| ContractError::InvalidProof {})?;
+    Ok(buf)
+}
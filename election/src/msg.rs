@@ -1,17 +1,260 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use std::cmp::Ordering;
+use std::fmt;
+
+use cosmwasm_std::{BlockInfo, Coin, CosmosMsg, Decimal, Timestamp, Uint128};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    pub start: u64,
-    pub end: u64,
+    pub start: Scheduled,
+    pub end: Scheduled,
     pub candidates: Vec<String>,
+    // quorum is the participation needed for the election to produce a winner
+    pub quorum: Option<Quorum>,
+    // threshold is the share of the weight cast for candidates, abstentions excluded,
+    // that every elected candidate must exceed or reach
+    pub threshold: Option<Threshold>,
+    // weight selects where voting power comes from, every ballot counts as 1 when unset
+    pub weight: Option<WeightSource>,
+    // mode selects how ballots are cast and counted, plurality when unset
+    pub mode: Option<VotingMode>,
+    // seats is the number of candidates elected, 1 when unset
+    pub seats: Option<u32>,
+    // registry restricts who may vote, anyone can vote when unset
+    pub registry: Option<Registry>,
+    // reveal_end makes ballots secret: voters commit to a ballot while voting is open
+    // and reveal it after end, until reveal_end
+    pub reveal_end: Option<Scheduled>,
+    // owner administers the election, the sender when unset
+    pub owner: Option<String>,
+    // actions are dispatched by Execute once their candidate has won
+    pub actions: Option<Vec<CandidateActions>>,
+    // nomination lets addresses nominate themselves until start, candidates may then be empty
+    pub nomination: Option<NominationConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    // owner administers a migrated election, it is required for versions up to 0.1.0
+    // which had no owner
+    pub owner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NominationConfig {
+    // deposit must be sent by every nominee
+    pub deposit: Coin,
+    // min_share is the share of the weight cast for candidates, abstentions excluded,
+    // a nominee needs to get the deposit back
+    pub min_share: Decimal,
+    // treasury receives the deposits of the nominees below min_share
+    pub treasury: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandidateActions {
+    pub candidate: String,
+    pub msgs: Vec<CosmosMsg>,
+}
+
+// Scheduled is a point of the voting window, given either as a block height or a block time.
+// All points of a window must be of the same kind.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Scheduled {
+    AtHeight(u64),
+    AtTime(Timestamp),
+}
+
+impl Scheduled {
+    // is_reached returns whether block is at or after this point
+    pub fn is_reached(&self, block: &BlockInfo) -> bool {
+        match self {
+            Scheduled::AtHeight(height) => block.height >= *height,
+            Scheduled::AtTime(time) => block.time >= *time,
+        }
+    }
+
+    // is_passed returns whether block is after this point
+    pub fn is_passed(&self, block: &BlockInfo) -> bool {
+        match self {
+            Scheduled::AtHeight(height) => block.height > *height,
+            Scheduled::AtTime(time) => block.time > *time,
+        }
+    }
+}
+
+// points of different kinds cannot be compared
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Scheduled::AtHeight(a), Scheduled::AtHeight(b)) => a.partial_cmp(b),
+            (Scheduled::AtTime(a), Scheduled::AtTime(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Scheduled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scheduled::AtHeight(height) => write!(f, "height {}", height),
+            Scheduled::AtTime(time) => write!(f, "time {}", time),
+        }
+    }
+}
+
+// Quorum counts every revealed ballot, abstentions included
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Quorum {
+    // Ballots is a minimum number of ballots
+    Ballots { count: u64 },
+    // Weight is a minimum total weight of the ballots
+    Weight { amount: Uint128 },
+    // Turnout is a minimum share of the registered voters, it needs a voter registry
+    Turnout { ratio: Decimal },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Threshold {
+    // MoreThan is met by a share above the ratio, a simple majority is more than 1/2
+    MoreThan { ratio: Decimal },
+    // AtLeast is met by the ratio itself, a supermajority of 2/3 is met by exactly two thirds
+    AtLeast { ratio: Decimal },
+}
+
+impl Threshold {
+    pub fn is_met(&self, share: Decimal) -> bool {
+        match self {
+            Threshold::MoreThan { ratio } => share > *ratio,
+            Threshold::AtLeast { ratio } => share >= *ratio,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Registry {
+    // Voters lists every eligible address
+    Voters { voters: Vec<String> },
+    // Merkle holds the hex encoded root of a merkle tree whose leaves are the sha256
+    // hashes of the eligible addresses, and the number of eligible addresses
+    Merkle { root: String, voters: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingMode {
+    // Plurality ballots name a single candidate
+    Plurality,
+    // RankedChoice ballots list candidates in order of preference,
+    // counted by instant runoff once voting has ended
+    RankedChoice,
+    // Approval ballots list every candidate the voter approves of,
+    // each of them receives the full weight of the ballot
+    Approval,
+}
+
+impl Default for VotingMode {
+    fn default() -> Self {
+        VotingMode::Plurality
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightSource {
+    // Snapshot weighs a ballot by the power the owner submitted for the voter before start,
+    // such as bank, staked or cw20 balances taken at the start height
+    Snapshot {},
+    // Cw4 weighs a ballot by the voter's weight in the cw4 group at address as of the start
+    // height, the voting window must be given in heights
+    Cw4 { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SnapshotPower {
+    pub address: String,
+    pub power: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    Vote { candidate: String },
+    // Vote casts a ballot, or replaces the ballot of the sender while voting is open.
+    // A ballot without candidates abstains.
+    // proof is required when eligibility is given by a merkle root
+    Vote {
+        candidates: Vec<String>,
+        proof: Option<Vec<String>>,
+    },
+    // Commit casts a secret ballot, commitment is the hex encoded sha256 hash of every
+    // candidate on the ballot followed by a zero byte, and then the salt
+    Commit {
+        commitment: String,
+        proof: Option<Vec<String>>,
+    },
+    // Reveal opens a committed ballot once voting has ended
+    Reveal {
+        candidates: Vec<String>,
+        salt: String,
+    },
+    // Delegate hands the voting power of the sender to delegate until the sender votes directly.
+    // Any ballot of the sender is withdrawn.
+    Delegate {
+        delegate: String,
+        proof: Option<Vec<String>>,
+    },
+    // Undelegate takes back the voting power handed to a delegate
+    Undelegate {},
+    // UpdateCandidates changes the candidates, only the owner can do it before voting starts
+    UpdateCandidates {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    // UpdateWindow moves the voting window, only the owner can do it before voting starts
+    UpdateWindow {
+        start: Scheduled,
+        end: Scheduled,
+    },
+    // Cancel stops the election for good, only the owner can do it before voting ends
+    Cancel {},
+    // UpdateVoters changes the listed voters, only the owner can do it before voting starts
+    UpdateVoters {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    // UpdateMerkleRoot replaces the merkle root, only the owner can do it before voting starts
+    UpdateMerkleRoot {
+        root: String,
+        voters: u64,
+    },
+    // SubmitSnapshot records the voting power of addresses for a snapshot election,
+    // only the owner can do it before voting starts. It can be sent in several batches
+    SubmitSnapshot {
+        powers: Vec<SnapshotPower>,
+    },
+    // Count counts the ballots of an ended election and stores the result, which finalizes
+    // the election. A ranked choice runoff is counted limit ballots at a time, so it may
+    // take several messages. Anyone can do it
+    Count {
+        limit: Option<u32>,
+    },
+    // Execute dispatches the actions of the elected candidates once the ballots were counted.
+    // Anyone can do it, but only once
+    Execute {},
+    // Nominate makes the sender a candidate before voting starts,
+    // the deposit of the nomination phase must be sent along
+    Nominate {},
+    // Finalize settles the deposits of the nominees once the election has ended: they are
+    // refunded to the nominees that reached the minimum share and sent to the treasury
+    // otherwise. Every deposit is refunded when the election was cancelled.
+    // Anyone can do it, but only once
+    Finalize {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -19,19 +262,185 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     // GetVoteInfo returns the current count as a json-encoded number
     GetVoteInfo {},
+    // GetWinner returns the outcome of the election once voting has ended
+    GetWinner {},
+    // GetRunoff returns the instant runoff rounds of a ranked choice election once voting has ended
+    GetRunoff {},
+    // IsEligible returns whether voter may vote
+    IsEligible {
+        voter: String,
+        proof: Option<Vec<String>>,
+    },
+    // GetTurnout returns the number of ballots cast compared with the registered voters
+    GetTurnout {},
+    // GetDelegation returns the delegate of voter and the voting power delegated to voter
+    GetDelegation {
+        voter: String,
+    },
+    // GetVotingPower returns the voting power of voter as of the start of voting,
+    // not counting delegations
+    GetVotingPower {
+        voter: String,
+    },
+    // GetActions returns the actions of candidate and whether the winning actions were executed
+    GetActions {
+        candidate: String,
+    },
+    // GetBallot returns the open ballot of voter, committed ballots stay hidden until revealed
+    GetBallot {
+        voter: String,
+    },
+    // ListBallots returns the open ballots of the voters after start_after, in address order
+    ListBallots {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // HasVoted returns whether voter has cast a ballot, committed or not
+    HasVoted {
+        voter: String,
+    },
+    // GetNomination returns the nomination phase and the nominees holding a deposit
+    GetNomination {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct VoteResponse {
-    pub start: u64,
-    pub end: u64,
+    pub status: Status,
+    pub start: Scheduled,
+    pub end: Scheduled,
     pub votes: Vec<Vote>,
+    // unrevealed is the number of committed ballots that have not been revealed
+    pub unrevealed: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    // Upcoming elections do not accept ballots yet
+    Upcoming,
+    Open,
+    // Revealing elections accept the reveal of committed ballots
+    Revealing,
+    Ended,
+    Cancelled,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Vote {
     pub candidate: String,
-    pub count: u32,
+    pub count: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BallotInfo {
+    pub voter: String,
+    // candidates in order of preference, none when the ballot abstains
+    pub candidates: Vec<String>,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BallotResponse {
+    pub ballot: Option<BallotInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BallotsResponse {
+    pub ballots: Vec<BallotInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NominationResponse {
+    pub deposit: Coin,
+    pub min_share: Decimal,
+    pub treasury: String,
+    // nominees are the candidates whose deposit has not been settled yet
+    pub nominees: Vec<String>,
+    pub settled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HasVotedResponse {
+    pub voted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibilityResponse {
+    pub eligible: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotingPowerResponse {
+    pub power: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ActionsResponse {
+    pub msgs: Vec<CosmosMsg>,
+    pub executed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegationResponse {
+    // delegate is unset when voter has not delegated
+    pub delegate: Option<String>,
+    pub weight: Uint128,
+    // delegated is the voting power delegated to voter by others
+    pub delegated: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TurnoutResponse {
+    pub ballots: u64,
+    // registered is unset when anyone can vote
+    pub registered: Option<u64>,
+    pub turnout: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RunoffResponse {
+    pub rounds: Vec<RunoffRound>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RunoffRound {
+    // votes of the candidates still in the race, highest first
+    pub votes: Vec<Vote>,
+    // exhausted is the weight of ballots without any remaining candidate
+    pub exhausted: Uint128,
+    // eliminated lists the candidates dropped at the end of the round
+    pub eliminated: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WinnerResponse {
+    pub ballots: u64,
+    // unrevealed committed ballots are discarded
+    pub unrevealed: u64,
+    // weight is the total weight of the ballots, abstained is the part of it that abstained
+    pub weight: Uint128,
+    pub abstained: Uint128,
+    // quorum_met and threshold_met are unset when the election has no such rule
+    // or was cancelled
+    pub quorum_met: Option<bool>,
+    pub threshold_met: Option<bool>,
+    pub outcome: Outcome,
+    // counted is set once Count stored the result, which Execute needs
+    pub counted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    // Elected holds the candidates filling every seat, highest first
+    Elected { winners: Vec<Vote> },
+    // Tie holds the candidates elected outright and those tied for the remaining seats
+    Tie { elected: Vec<Vote>, tied: Vec<Vote> },
+    QuorumNotMet { quorum: Quorum },
+    // ThresholdNotMet is reported when a candidate that would be elected does not meet
+    // the threshold
+    ThresholdNotMet { threshold: Threshold },
+    Cancelled {},
 }
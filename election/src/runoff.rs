@@ -0,0 +1,104 @@
+use std::cmp::Reverse;
+
+use cosmwasm_std::Uint128;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::{RunoffRound, Vote};
+use crate::state::Ballot;
+
+// Runoff is an instant runoff counted ballot by ballot, so that it can be spread over
+// several messages. Every ballot counts towards its highest ranked candidate still in
+// the race, and the candidates with the fewest votes are eliminated until one holds a
+// majority of the ballots that are not exhausted.
+// All candidates tied for last place are eliminated together, unless that would
+// eliminate every remaining candidate, in which case the runoff ends in a tie.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Runoff {
+    // candidates still in the race
+    pub remaining: Vec<String>,
+    // counts of the round in progress, in the order of remaining
+    pub counts: Vec<Uint128>,
+    pub exhausted: Uint128,
+    // rounds holds the rounds counted so far
+    pub rounds: Vec<RunoffRound>,
+}
+
+impl Runoff {
+    pub fn new(candidates: &[String]) -> Self {
+        Runoff {
+            remaining: candidates.to_vec(),
+            counts: vec![Uint128::zero(); candidates.len()],
+            exhausted: Uint128::zero(),
+            rounds: Vec::new(),
+        }
+    }
+
+    // count adds a ballot to the round in progress
+    pub fn count(&mut self, ballot: &Ballot) {
+        let remaining = &self.remaining;
+        let preference = ballot
+            .candidates
+            .iter()
+            .find_map(|candidate| remaining.iter().position(|c| c == candidate));
+        match preference {
+            Some(i) => self.counts[i] += ballot.weight,
+            None => self.exhausted += ballot.weight,
+        }
+    }
+
+    // end_round closes the round in progress once every ballot was counted in it,
+    // and returns whether the runoff is finished
+    pub fn end_round(&mut self) -> bool {
+        let active: Uint128 = self.counts.iter().sum();
+        let mut votes: Vec<Vote> = self
+            .remaining
+            .iter()
+            .zip(&self.counts)
+            .map(|(candidate, count)| Vote {
+                candidate: candidate.clone(),
+                count: *count,
+            })
+            .collect();
+        votes.sort_by_key(|vote| Reverse(vote.count));
+
+        let lowest = votes.last().map(|vote| vote.count).unwrap_or_default();
+        let majority = votes
+            .first()
+            .map_or(false, |vote| vote.count + vote.count > active);
+        let finished = majority || votes.iter().all(|vote| vote.count == lowest);
+        let eliminated: Vec<String> = if finished {
+            Vec::new()
+        } else {
+            votes
+                .iter()
+                .filter(|vote| vote.count == lowest)
+                .map(|vote| vote.candidate.clone())
+                .collect()
+        };
+        self.remaining
+            .retain(|candidate| !eliminated.contains(candidate));
+        self.counts = vec![Uint128::zero(); self.remaining.len()];
+
+        self.rounds.push(RunoffRound {
+            votes,
+            exhausted: self.exhausted,
+            eliminated,
+        });
+        self.exhausted = Uint128::zero();
+        finished
+    }
+}
+
+// instant_runoff counts the ballots round by round until the runoff is finished
+pub fn instant_runoff(candidates: &[String], ballots: &[Ballot]) -> Vec<RunoffRound> {
+    let mut runoff = Runoff::new(candidates);
+    loop {
+        for ballot in ballots {
+            runoff.count(ballot);
+        }
+        if runoff.end_round() {
+            return runoff.rounds;
+        }
+    }
+}
@@ -1,10 +1,114 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, BlockInfo, Coin, CosmosMsg, Decimal, Empty, Uint128};
+use cw_storage_plus::{Item, Map};
+
+use crate::msg::{Quorum, Scheduled, Status, Threshold, Vote, VotingMode, WeightSource};
+use crate::runoff::Runoff;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
+    pub owner: Addr,
+    pub start: Scheduled,
+    pub end: Scheduled,
+    pub candidates: Vec<String>,
+    pub quorum: Option<Quorum>,
+    pub threshold: Option<Threshold>,
+    pub weight: Option<WeightSource>,
+    pub mode: VotingMode,
+    pub seats: u32,
+    pub registry: Option<VoterRegistry>,
+    pub reveal_end: Option<Scheduled>,
+    pub cancelled: bool,
+    // executed is set once the actions of the elected candidates were dispatched
+    pub executed: bool,
+    pub nomination: Option<Nomination>,
+}
+
+impl State {
+    // closes_at returns the last point at which ballots can change the results
+    pub fn closes_at(&self) -> Scheduled {
+        self.reveal_end.unwrap_or(self.end)
+    }
+
+    pub fn status(&self, block: &BlockInfo) -> Status {
+        if self.cancelled {
+            Status::Cancelled
+        } else if !self.start.is_reached(block) {
+            Status::Upcoming
+        } else if !self.end.is_passed(block) {
+            Status::Open
+        } else if !self.closes_at().is_passed(block) {
+            Status::Revealing
+        } else {
+            Status::Ended
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Nomination {
+    pub deposit: Coin,
+    pub min_share: Decimal,
+    pub treasury: Addr,
+    // settled is set once Finalize paid out the deposits
+    pub settled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Commitment {
+    pub hash: String,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoterRegistry {
+    // Listed voters are kept in VOTERS
+    Listed { voters: u64 },
+    Merkle { root: String, voters: u64 },
+}
+
+impl VoterRegistry {
+    pub fn voters(&self) -> u64 {
+        match self {
+            VoterRegistry::Listed { voters } | VoterRegistry::Merkle { voters, .. } => *voters,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ballot {
+    // candidates in order of preference
+    pub candidates: Vec<String>,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, JsonSchema)]
+pub struct Totals {
+    // weight of every ballot
+    pub weight: Uint128,
+    // weight of the ballots that abstained
+    pub abstained: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Delegation {
+    pub delegate: Addr,
+    // voting power of the delegator when they delegated
+    pub weight: Uint128,
+}
+
+pub const STATE: Item<State> = Item::new("state");
+
+// STATUS holds the status of the election as of the last executed message,
+// so that status changes can be reported as events
+pub const STATUS: Item<Status> = Item::new("status");
+
+// LegacyState is the layout of STATE up to version 0.1.0, which kept every vote in it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
     pub start: u64,
     pub end: u64,
     pub candidates: Vec<String>,
@@ -17,4 +121,91 @@ pub struct VoteInfo {
     pub candidate: String,
 }
 
-pub const STATE: Item<State> = Item::new("state");
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
+
+// StateV2 is the layout of STATE in version 0.2, whose threshold was a ratio that
+// candidates met by reaching it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateV2 {
+    pub owner: Addr,
+    pub start: Scheduled,
+    pub end: Scheduled,
+    pub candidates: Vec<String>,
+    pub quorum: Option<Quorum>,
+    pub threshold: Option<Decimal>,
+    pub weight: Option<WeightSourceV2>,
+    pub mode: VotingMode,
+    pub seats: u32,
+    pub registry: Option<VoterRegistry>,
+    pub reveal_end: Option<Scheduled>,
+    pub cancelled: bool,
+    pub executed: bool,
+    pub nomination: Option<Nomination>,
+}
+
+pub const STATE_V2: Item<StateV2> = Item::new("state");
+
+// WeightSourceV2 is WeightSource in version 0.2, which read native, staked and cw20
+// weights live when voting
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightSourceV2 {
+    Native { denom: String },
+    Staked {},
+    Cw20 { address: String },
+    Snapshot {},
+    Cw4 { address: String },
+}
+
+// BALLOT_COUNT holds the number of ballots cast so far
+pub const BALLOT_COUNT: Item<u64> = Item::new("ballot_count");
+
+// VOTERS holds the eligible voters of a listed registry
+pub const VOTERS: Map<&Addr, Empty> = Map::new("voters");
+
+// UNREVEALED holds the number of committed ballots that have not been revealed
+pub const UNREVEALED: Item<u64> = Item::new("unrevealed");
+
+// COMMITMENTS holds the secret ballots committed by each voter until they are revealed
+pub const COMMITMENTS: Map<&Addr, Commitment> = Map::new("commitments");
+
+// BALLOTS holds the ballot cast by each voter
+pub const BALLOTS: Map<&Addr, Ballot> = Map::new("ballots");
+
+// DELEGATIONS holds the delegation made by each delegator
+pub const DELEGATIONS: Map<&Addr, Delegation> = Map::new("delegations");
+
+// DELEGATED holds the voting power delegated to each delegate.
+// It is carried by the ballot of the delegate, if any.
+pub const DELEGATED: Map<&Addr, Uint128> = Map::new("delegated");
+
+// SNAPSHOT holds the voting power submitted by the owner for each address
+pub const SNAPSHOT: Map<&Addr, Uint128> = Map::new("snapshot");
+
+// ACTIONS holds the messages dispatched when a candidate wins
+pub const ACTIONS: Map<&str, Vec<CosmosMsg>> = Map::new("actions");
+
+// DEPOSITS holds the deposit of each nominee until it is settled
+pub const DEPOSITS: Map<&Addr, Uint128> = Map::new("deposits");
+
+// TOTALS holds the running sum of ballot weights, updated with TALLY
+pub const TOTALS: Item<Totals> = Item::new("totals");
+
+// TALLY holds the running sum of ballot weights of each candidate, updated on every ballot.
+// Ranked choice ballots only count towards their first preference here.
+// Approval ballots count towards every approved candidate.
+pub const TALLY: Map<&str, Uint128> = Map::new("tally");
+
+// Counting holds the ranked choice count of an ended election while Count advances it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Counting {
+    pub runoff: Runoff,
+    // counted_to is the last voter whose ballot was counted in the round in progress
+    pub counted_to: Option<Addr>,
+}
+
+pub const COUNTING: Item<Counting> = Item::new("counting");
+
+// RESULT holds the votes the seats are filled from, stored by Count once every ballot
+// was counted
+pub const RESULT: Item<Vec<Vote>> = Item::new("result");
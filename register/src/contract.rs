@@ -1,11 +1,11 @@
 use cosmwasm_std::{
-    to_binary, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, HandleResponse, HumanAddr,
-    InitResponse, MessageInfo, StdResult, WasmMsg,
+    to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, HandleResponse, HumanAddr, InitResponse,
+    MessageInfo, StdResult, WasmMsg,
 };
 
 use crate::error::ContractError;
-use crate::msg::{CountResponse, HandleMsg, InitMsg, ProxyCall, QueryMsg};
-use crate::state::{config, State};
+use crate::msg::{CallOptions, HandleMsg, InitMsg, ProxyCall, QueryMsg, RegistrationResponse};
+use crate::state::{config, registrations, registrations_read, State};
 
 // Note, you can use StdResult in some functions where you do not
 // make use of the custom errors
@@ -25,23 +25,38 @@ pub fn init(
 pub fn handle(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: HandleMsg,
 ) -> Result<HandleResponse, ContractError> {
     match msg {
-        HandleMsg::Register { collector } => try_register(deps, env, collector),
+        HandleMsg::Register {
+            collector,
+            callback,
+        } => try_register(deps, env, collector, callback),
+        HandleMsg::Registered {} => try_registered(deps, info),
     }
 }
 
 pub fn try_register(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
     collector: HumanAddr,
+    callback: Option<bool>,
 ) -> Result<HandleResponse, ContractError> {
-    let msg = to_binary(&ProxyCall { call: Empty {} })?;
+    let callback = callback.unwrap_or(false);
+    // keep a notification received for an earlier registration
+    let key = collector.as_bytes();
+    if callback && registrations_read(deps.storage).may_load(key)?.is_none() {
+        registrations(deps.storage).save(key, &false)?;
+    }
+    let msg = to_binary(&ProxyCall {
+        call: CallOptions {
+            callback: if callback { Some(true) } else { None },
+        },
+    })?;
     let msgs = vec![CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: collector,
-        msg: msg,
+        msg,
         send: vec![],
     })];
     Ok(HandleResponse {
@@ -51,20 +66,36 @@ pub fn try_register(
     })
 }
 
-pub fn query(deps: Deps, _env: Env, _msg: QueryMsg) -> StdResult<Binary> {
-    to_binary(&query_count(deps)?)
+// try_registered records the notification of a collector this contract registered with
+pub fn try_registered(deps: DepsMut, info: MessageInfo) -> Result<HandleResponse, ContractError> {
+    let key = info.sender.as_bytes();
+    if registrations_read(deps.storage).may_load(key)?.is_none() {
+        return Err(ContractError::Unauthorized {});
+    }
+    registrations(deps.storage).save(key, &true)?;
+
+    Ok(HandleResponse::default())
 }
 
-fn query_count(_deps: Deps) -> StdResult<CountResponse> {
-    Ok(CountResponse {})
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetRegistration { collector } => to_binary(&query_registration(deps, collector)?),
+    }
+}
+
+fn query_registration(deps: Deps, collector: HumanAddr) -> StdResult<RegistrationResponse> {
+    let registered = registrations_read(deps.storage).may_load(collector.as_bytes())?;
+    Ok(RegistrationResponse {
+        requested: registered.is_some(),
+        registered: registered.unwrap_or(false),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cosmwasm_std::coins;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{to_binary, Empty};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{coins, from_binary, from_slice};
 
     #[test]
     fn try_register() {
@@ -77,14 +108,103 @@ mod tests {
         let res = init(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
 
+        let registration = |deps: Deps, collector: &str| -> RegistrationResponse {
+            let msg = QueryMsg::GetRegistration {
+                collector: collector.into(),
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // collectors are only asked for a callback when the sender opts in
+        let info = mock_info("anyone", &coins(2, "token"));
+        let msg = HandleMsg::Register {
+            collector: HumanAddr::from("plain"),
+            callback: None,
+        };
+        let res = handle(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "plain".into(),
+                msg: Binary::from(br#"{"call":{"callback":null}}"#.to_vec()),
+                send: vec![],
+            })],
+            res.messages
+        );
+        assert!(!registration(deps.as_ref(), "plain").requested);
+        let info = mock_info("plain", &[]);
+        let err = handle(deps.as_mut(), mock_env(), info, HandleMsg::Registered {}).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
         let info = mock_info("anyone", &coins(2, "token"));
         let msg = HandleMsg::Register {
             collector: HumanAddr::from("collector"),
+            callback: Some(true),
         };
         let res = handle(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(1, res.messages.len());
+        let value = registration(deps.as_ref(), "collector");
+        assert!(value.requested);
+        assert!(!value.registered);
+
+        // only collectors this contract registered with can notify it
+        let info = mock_info("other", &[]);
+        let err = handle(deps.as_mut(), mock_env(), info, HandleMsg::Registered {}).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let info = mock_info("collector", &[]);
+        handle(deps.as_mut(), mock_env(), info, HandleMsg::Registered {}).unwrap();
+        assert!(registration(deps.as_ref(), "collector").registered);
+    }
+
+    #[test]
+    fn collector_callback() {
+        let mut deps = mock_dependencies(&[]);
+        init(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            InitMsg {},
+        )
+        .unwrap();
+        let mut collector_deps = mock_dependencies(&[]);
+        let msg = collector::msg::InitMsg {
+            hook: Some(to_binary(&HandleMsg::Registered {}).unwrap()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        collector::contract::init(collector_deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let msg = to_binary(&ProxyCall { call: Empty {} }).unwrap();
-        println!("{}", msg)
+        // deliver the call of this contract to the collector
+        let msg = HandleMsg::Register {
+            collector: "collector".into(),
+            callback: Some(true),
+        };
+        let res = handle(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        let call = match &res.messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => msg,
+            msg => panic!("unexpected message: {:?}", msg),
+        };
+        let call: collector::msg::HandleMsg = from_slice(call).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res =
+            collector::contract::handle(collector_deps.as_mut(), mock_env(), info, call).unwrap();
+
+        // and the hook back to this contract
+        let hook = match &res.messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!(MOCK_CONTRACT_ADDR, contract_addr.as_str());
+                msg
+            }
+            msg => panic!("unexpected message: {:?}", msg),
+        };
+        let info = mock_info("collector", &[]);
+        handle(deps.as_mut(), mock_env(), info, from_slice(hook).unwrap()).unwrap();
+        let msg = QueryMsg::GetRegistration {
+            collector: "collector".into(),
+        };
+        let value: RegistrationResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(value.registered);
     }
 }
@@ -10,16 +10,36 @@ pub struct ProxyCall<T> {
     pub call: T,
 }
 
+// CallOptions are the options of the call sent to a collector
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CallOptions {
+    pub callback: Option<bool>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
-    Register { collector: HumanAddr },
+    // Register calls collector. callback asks collector to notify this contract, which
+    // only works when collector is configured with {"registered":{}} as its hook
+    Register {
+        collector: HumanAddr,
+        callback: Option<bool>,
+    },
+    // Registered is sent back by a collector once it registered this contract
+    Registered {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    // GetRegistration returns whether this contract asked collector for a callback
+    // and whether collector notified the registration
+    GetRegistration { collector: HumanAddr },
+}
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CountResponse {}
+pub struct RegistrationResponse {
+    pub requested: bool,
+    pub registered: bool,
+}
@@ -2,17 +2,31 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::Storage;
-use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
+use cosmwasm_storage::{
+    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
+    Singleton,
+};
 
 pub static CONFIG_KEY: &[u8] = b"config";
+pub static REGISTRATIONS_KEY: &[u8] = b"registrations";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {}
 
-pub fn config(storage: &mut dyn Storage) -> Singleton<State> {
+pub fn config(storage: &mut dyn Storage) -> Singleton<'_, State> {
     singleton(storage, CONFIG_KEY)
 }
 
-pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<State> {
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, State> {
     singleton_read(storage, CONFIG_KEY)
 }
+
+// registrations is keyed by the collectors this contract registered with,
+// the value is set once the collector notified the registration
+pub fn registrations(storage: &mut dyn Storage) -> Bucket<'_, bool> {
+    bucket(storage, REGISTRATIONS_KEY)
+}
+
+pub fn registrations_read(storage: &dyn Storage) -> ReadonlyBucket<'_, bool> {
+    bucket_read(storage, REGISTRATIONS_KEY)
+}